@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use crate::export::{self, Export, Record};
+use crate::harness::CacheMode;
+use crate::stats::{mann_whitney_u, mean, min_p_value};
+
+pub fn path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.json"))
+}
+
+pub fn save(dir: &Path, name: &str, export: &Export) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = path(dir, name);
+    export::write_json(&path, export)?;
+    Ok(path)
+}
+
+pub fn load(dir: &Path, name: &str) -> std::io::Result<Export> {
+    export::read_json(&path(dir, name))
+}
+
+/// How a case performs now compared to the baseline
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub sort: String,
+    pub distribution: String,
+    pub length: usize,
+    pub element_type: String,
+    pub cache: CacheMode,
+    pub baseline_ns: f64,
+    pub current_ns: f64,
+    /// Above 1 when the current run is faster
+    pub speedup: f64,
+    pub p_value: f64,
+    /// Whether either side had so few samples that no difference could be significant at level
+    /// `alpha`, leaving the threshold as the only judge
+    pub untestable: bool,
+    pub regressed: bool,
+}
+
+/// Pairs up every current case with the same case in the baseline.
+///
+/// A case regresses when it got slower by more than `threshold` (a fraction, 0.05 being 5%) and
+/// the difference is significant at level `alpha`. When there are too few samples for any
+/// difference to be significant, the threshold alone decides.
+pub fn compare(
+    baseline: &[Record],
+    current: &[Record],
+    threshold: f64,
+    alpha: f64,
+) -> Vec<Comparison> {
+    current
+        .iter()
+        .filter_map(|cur| {
+            let base = baseline.iter().find(|b| {
+                b.sort == cur.sort
+                    && b.distribution == cur.distribution
+                    && b.length == cur.length
+                    && b.element_type == cur.element_type
+                    && b.cache == cur.cache
+            })?;
+            let base_samples: Vec<f64> = base.samples_ns.iter().map(|&s| s as f64).collect();
+            let cur_samples: Vec<f64> = cur.samples_ns.iter().map(|&s| s as f64).collect();
+            let baseline_ns = mean(&base_samples);
+            let current_ns = mean(&cur_samples);
+            let speedup = baseline_ns / current_ns;
+            let p_value = mann_whitney_u(&base_samples, &cur_samples);
+            let untestable = min_p_value(base_samples.len(), cur_samples.len()) >= alpha;
+            let slower = speedup < 1. / (1. + threshold);
+            Some(Comparison {
+                sort: cur.sort.clone(),
+                distribution: cur.distribution.clone(),
+                length: cur.length,
+                element_type: cur.element_type.clone(),
+                cache: cur.cache,
+                baseline_ns,
+                current_ns,
+                speedup,
+                p_value,
+                untestable,
+                regressed: slower && (untestable || p_value < alpha),
+            })
+        })
+        .collect()
+}
+
+pub fn print_comparisons(name: &str, comparisons: &[Comparison], alpha: f64) {
+    println!("Compared to baseline `{name}`:");
+    let sort_width = comparisons.iter().map(|c| c.sort.len()).max().unwrap_or(0);
+    let dist_width = comparisons
+        .iter()
+        .map(|c| c.distribution.len())
+        .max()
+        .unwrap_or(0);
+    for c in comparisons {
+        let verdict = if c.regressed && c.untestable {
+            "REGRESSED (too few runs to test)"
+        } else if c.regressed {
+            "REGRESSED"
+        } else if c.untestable {
+            "too few runs to test"
+        } else if c.p_value >= alpha {
+            "no significant change"
+        } else if c.speedup > 1. {
+            "faster"
+        } else {
+            "slower"
+        };
+        println!(
+            "    {:sort_width$}  {:dist_width$}  {:>9} {} {}  {:>12.0}ns -> {:>12.0}ns  {:>6.3}x  p={:.3}  {verdict}",
+            c.sort,
+            c.distribution,
+            c.length,
+            c.element_type,
+            c.cache.name(),
+            c.baseline_ns,
+            c.current_ns,
+            c.speedup,
+            c.p_value,
+        );
+    }
+}
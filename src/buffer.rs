@@ -0,0 +1,43 @@
+/// The memory every run sorts in, refilled from the pristine input before each run so that all
+/// runs see their input at the same, aligned address
+pub struct AlignedBuffer<T> {
+    storage: Vec<T>,
+    /// In bytes, always a power of two
+    alignment: usize,
+}
+
+impl<T: Clone> AlignedBuffer<T> {
+    /// A buffer that fits `capacity` elements without reallocating
+    pub fn new(capacity: usize, alignment: usize) -> Self {
+        AlignedBuffer {
+            storage: Vec::with_capacity(capacity + Self::padding(alignment)),
+            alignment,
+        }
+    }
+
+    /// The most elements that could be needed in front of the data to align it
+    fn padding(alignment: usize) -> usize {
+        alignment / std::mem::size_of::<T>().max(1)
+    }
+
+    /// Replaces the contents of the buffer with a copy of `data`, returning the copy
+    pub fn fill(&mut self, data: &[T]) -> &mut [T] {
+        let Some(first) = data.first() else {
+            return &mut [];
+        };
+        let padding = Self::padding(self.alignment);
+        if self.storage.capacity() < data.len() + padding {
+            self.storage = Vec::with_capacity(data.len() + padding);
+        }
+        self.storage.clear();
+        // Either the data is already aligned for T or the alignment can't be reached, in both cases
+        // there's nothing better than the start of the allocation
+        let offset = match self.storage.as_ptr().align_offset(self.alignment) {
+            offset if offset <= padding => offset,
+            _ => 0,
+        };
+        self.storage.resize(offset, first.clone());
+        self.storage.extend_from_slice(data);
+        &mut self.storage[offset..]
+    }
+}
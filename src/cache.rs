@@ -0,0 +1,64 @@
+use std::hint::black_box;
+
+/// Assumed when the cache sizes can't be read from sysfs
+const DEFAULT_CACHE_BYTES: usize = 32 << 20;
+const CACHE_LINE: usize = 64;
+
+/// The size of the largest CPU cache, as reported by Linux
+pub fn last_level_cache_bytes() -> Option<usize> {
+    let caches = std::fs::read_dir("/sys/devices/system/cpu/cpu0/cache").ok()?;
+    caches
+        .filter_map(|entry| {
+            let size = std::fs::read_to_string(entry.ok()?.path().join("size")).ok()?;
+            parse_size(size.trim())
+        })
+        .max()
+}
+
+/// Parses sizes like `32K` or `8M`
+fn parse_size(size: &str) -> Option<usize> {
+    let (digits, unit) = match size.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => size.split_at(idx),
+        None => (size, ""),
+    };
+    let shift = match unit {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        _ => return None,
+    };
+    Some(digits.parse::<usize>().ok()? << shift)
+}
+
+/// Evicts everything from the CPU caches by writing to a buffer larger than all of them
+pub struct CacheFlusher {
+    buffer: Vec<u8>,
+}
+
+impl Default for CacheFlusher {
+    fn default() -> Self {
+        // Twice the size, so that whatever the replacement policy nothing else survives
+        let size = 2 * last_level_cache_bytes().unwrap_or(DEFAULT_CACHE_BYTES);
+        CacheFlusher {
+            buffer: vec![0; size],
+        }
+    }
+}
+
+impl CacheFlusher {
+    pub fn flush(&mut self) {
+        for byte in self.buffer.iter_mut().step_by(CACHE_LINE) {
+            *byte = byte.wrapping_add(1);
+        }
+        black_box(&mut self.buffer);
+    }
+}
+
+/// Reads every cache line of `data`, so as much of it as fits is cached
+pub fn warm<T: Clone>(data: &[T]) {
+    let step = (CACHE_LINE / std::mem::size_of::<T>().max(1)).max(1);
+    for item in data.iter().step_by(step) {
+        black_box(item.clone());
+    }
+}
@@ -0,0 +1,272 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::harness::CacheMode;
+use crate::isolate::Isolation;
+use crate::plot::{Chart, Theme};
+use crate::report::Metric;
+use crate::suite::{DistributionSpec, ElementType, OutputFormat, Suite};
+
+/// The default runs when saving or comparing against a baseline, enough for a change to be
+/// significant at the default `--significance`
+const BASELINE_RUNS: usize = 10;
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Benchmark sorting algorithms over a range of input distributions"
+)]
+pub struct Args {
+    /// Run the suite described by this TOML file, the benchmark and output options given alongside
+    /// it take precedence
+    #[arg(long, value_name = "FILE")]
+    pub suite: Option<PathBuf>,
+    /// Sorting methods to run, by name (defaults to all of them)
+    #[arg(short, long = "sort", value_name = "NAME")]
+    pub sorts: Vec<String>,
+    /// Input distributions to generate, by name (defaults to all of them)
+    #[arg(short, long = "distribution", value_name = "NAME")]
+    pub distributions: Vec<String>,
+    /// Only run sorting methods whose name matches this pattern, `*` and `?` are wildcards
+    #[arg(long, value_name = "PATTERN")]
+    pub sort_filter: Option<String>,
+    /// Only generate input distributions whose name matches this pattern, `*` and `?` are wildcards
+    #[arg(long, value_name = "PATTERN")]
+    pub distribution_filter: Option<String>,
+    /// The input lengths to benchmark [default: 100,1_000,10_000]
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_length)]
+    pub lengths: Vec<usize>,
+    /// The type of the elements being sorted [default: i32]
+    #[arg(short = 't', long = "type", value_enum)]
+    pub element_type: Option<ElementType>,
+    /// How many times each input is sorted [default: 2, or 10 with --baseline or --save-baseline]
+    #[arg(short, long, value_parser = parse_runs)]
+    pub runs: Option<usize>,
+    /// Seed for input generation, a random one is picked if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Color theme of the charts [default: dark]
+    #[arg(long, value_enum)]
+    pub theme: Option<Theme>,
+    /// What the charts plot [default: time-per-element]
+    #[arg(long, value_enum)]
+    pub metric: Option<Metric>,
+    /// Plot bar and box charts on a logarithmic axis, so quadratic sorts don't flatten the rest
+    #[arg(long)]
+    pub log_scale: bool,
+    /// The kinds of chart drawn for the png, svg and html formats [default: bar,heatmap,scaling]
+    #[arg(long = "chart", value_enum, value_delimiter = ',', value_name = "KIND")]
+    pub charts: Vec<Chart>,
+    /// Collect branch misses, cache misses, instructions and cycles for every run through
+    /// perf_event_open
+    #[arg(long)]
+    pub counters: bool,
+    /// Run benchmark cases in separate processes, so they can't affect each other's measurements
+    /// [default: none]
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub isolate: Option<Isolation>,
+    /// Whether inputs are cached when a run starts, both modes can be given to chart each regime
+    /// [default: warm]
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "MODE")]
+    pub cache: Vec<CacheMode>,
+    /// Align the buffer inputs are sorted in to this many bytes, a power of two [default: 64]
+    #[arg(long, value_name = "BYTES", value_parser = parse_alignment)]
+    pub alignment: Option<usize>,
+    /// Pin the benchmark to this CPU core
+    #[arg(long, value_name = "CORE")]
+    pub pin_core: Option<usize>,
+    /// Lower the niceness of the benchmark as far as permitted
+    #[arg(long)]
+    pub raise_priority: bool,
+    /// Run a single job sent over stdin, used by --isolate
+    #[arg(long, hide = true)]
+    pub worker: bool,
+    /// The kinds of output to produce [default: png]
+    #[arg(
+        long = "format",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FORMAT"
+    )]
+    pub formats: Vec<OutputFormat>,
+    /// The sorting method every other one is compared against
+    #[arg(long, value_name = "SORT")]
+    pub reference: Option<String>,
+    /// Draw a bar next to every result of the table format
+    #[arg(long)]
+    pub bars: bool,
+    /// The size of the charts in pixels [default: 1920x1080]
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_resolution)]
+    pub resolution: Option<(u32, u32)>,
+    /// Directory the results are written to [default: images]
+    #[arg(short, long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+    /// Save the results as a baseline with this name
+    #[arg(long, value_name = "NAME")]
+    pub save_baseline: Option<String>,
+    /// Compare the results against the baseline with this name, exiting with an error if any
+    /// case regressed
+    #[arg(long, value_name = "NAME")]
+    pub baseline: Option<String>,
+    /// Directory baselines are saved to and loaded from
+    #[arg(long, value_name = "DIR", default_value = "baselines")]
+    pub baseline_dir: PathBuf,
+    /// How much slower than the baseline a case may get, in percent, before it counts as a
+    /// regression
+    #[arg(long, value_name = "PERCENT", default_value_t = 5.)]
+    pub regression_threshold: f64,
+    /// The p-value below which a change from the baseline is considered significant
+    #[arg(long, value_name = "P", default_value_t = 0.05)]
+    pub significance: f64,
+    /// List the available sorting methods and input distributions, then exit
+    #[arg(long)]
+    pub list: bool,
+}
+
+fn parse_resolution(s: &str) -> Result<(u32, u32), String> {
+    let (width, height) = s
+        .split_once('x')
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got `{s}`"))?;
+    let parse = |n: &str| match n.parse::<u32>() {
+        Ok(0) => Err("the resolution can't be zero".to_string()),
+        Ok(n) => Ok(n),
+        Err(e) => Err(format!("{e}")),
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+fn parse_length(s: &str) -> Result<usize, String> {
+    s.replace('_', "").parse().map_err(|e| format!("{e}"))
+}
+
+fn parse_runs(s: &str) -> Result<usize, String> {
+    let runs: usize = s.parse().map_err(|e| format!("{e}"))?;
+    if runs == 0 {
+        return Err("every input has to be sorted at least once".to_string());
+    }
+    Ok(runs)
+}
+
+fn parse_alignment(s: &str) -> Result<usize, String> {
+    let alignment: usize = s.parse().map_err(|e| format!("{e}"))?;
+    if !alignment.is_power_of_two() {
+        return Err(format!("{alignment} is not a power of two"));
+    }
+    Ok(alignment)
+}
+
+impl Args {
+    /// The suite to run, loaded from `--suite` if given, with the rest of the options applied on top
+    pub fn suite(&self) -> Result<Suite, String> {
+        let Some(path) = &self.suite else {
+            return Ok(Suite {
+                lengths: if self.lengths.is_empty() {
+                    Suite::default().lengths
+                } else {
+                    self.lengths.clone()
+                },
+                element_types: self
+                    .element_type
+                    .map_or_else(|| Suite::default().element_types, |t| vec![t]),
+                runs: self.runs.unwrap_or_else(|| {
+                    if self.baseline.is_some() || self.save_baseline.is_some() {
+                        BASELINE_RUNS
+                    } else {
+                        Suite::default().runs
+                    }
+                }),
+                seed: self.seed,
+                sorts: self.sorts.clone(),
+                distributions: self
+                    .distributions
+                    .iter()
+                    .cloned()
+                    .map(DistributionSpec::Name)
+                    .collect(),
+                sort_filter: self.sort_filter.clone(),
+                distribution_filter: self.distribution_filter.clone(),
+                formats: if self.formats.is_empty() {
+                    Suite::default().formats
+                } else {
+                    self.formats.clone()
+                },
+                metric: self.metric.unwrap_or_default(),
+                charts: if self.charts.is_empty() {
+                    Suite::default().charts
+                } else {
+                    self.charts.clone()
+                },
+                log_scale: self.log_scale,
+                counters: self.counters,
+                isolation: self.isolate.unwrap_or_default(),
+                cache: if self.cache.is_empty() {
+                    Suite::default().cache
+                } else {
+                    self.cache.clone()
+                },
+                alignment: self.alignment.unwrap_or_else(|| Suite::default().alignment),
+                pin_core: self.pin_core,
+                raise_priority: self.raise_priority,
+                theme: self.theme.unwrap_or_default(),
+                reference: self.reference.clone(),
+                bars: self.bars,
+                resolution: self
+                    .resolution
+                    .unwrap_or_else(|| Suite::default().resolution),
+                output: self
+                    .output
+                    .clone()
+                    .unwrap_or_else(|| Suite::default().output),
+                ..Suite::default()
+            });
+        };
+        let mut suite = Suite::load(path)?;
+        if !self.lengths.is_empty() {
+            suite.lengths = self.lengths.clone();
+        }
+        if let Some(element_type) = self.element_type {
+            suite.element_types = vec![element_type];
+        }
+        suite.runs = self.runs.unwrap_or(suite.runs);
+        if !self.formats.is_empty() {
+            suite.formats = self.formats.clone();
+        }
+        suite.seed = self.seed.or(suite.seed);
+        suite.theme = self.theme.unwrap_or(suite.theme);
+        suite.resolution = self.resolution.unwrap_or(suite.resolution);
+        suite.reference = self.reference.clone().or(suite.reference);
+        suite.bars |= self.bars;
+        suite.metric = self.metric.unwrap_or(suite.metric);
+        if !self.charts.is_empty() {
+            suite.charts = self.charts.clone();
+        }
+        suite.log_scale |= self.log_scale;
+        suite.counters |= self.counters;
+        suite.isolation = self.isolate.unwrap_or(suite.isolation);
+        if !self.cache.is_empty() {
+            suite.cache = self.cache.clone();
+        }
+        suite.alignment = self.alignment.unwrap_or(suite.alignment);
+        suite.pin_core = self.pin_core.or(suite.pin_core);
+        suite.raise_priority |= self.raise_priority;
+        suite.output = self.output.clone().unwrap_or(suite.output);
+        if !self.sorts.is_empty() {
+            suite.sorts = self.sorts.clone();
+        }
+        if !self.distributions.is_empty() {
+            suite.distributions = self
+                .distributions
+                .iter()
+                .cloned()
+                .map(DistributionSpec::Name)
+                .collect();
+        }
+        suite.sort_filter = self.sort_filter.clone().or(suite.sort_filter);
+        suite.distribution_filter = self
+            .distribution_filter
+            .clone()
+            .or(suite.distribution_filter);
+        Ok(suite)
+    }
+}
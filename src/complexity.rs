@@ -0,0 +1,122 @@
+use crate::harness::CacheMode;
+use crate::report::BenchmarkReport;
+use crate::stats::{mean, slope};
+
+/// How the runtime of a sort grows with the length of its input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    Linear,
+    Linearithmic,
+    Quadratic,
+}
+
+const MODELS: [Model; 3] = [Model::Linear, Model::Linearithmic, Model::Quadratic];
+
+impl Model {
+    pub fn name(self) -> &'static str {
+        match self {
+            Model::Linear => "n",
+            Model::Linearithmic => "n log n",
+            Model::Quadratic => "n²",
+        }
+    }
+
+    fn eval(self, n: f64) -> f64 {
+        match self {
+            Model::Linear => n,
+            // Clamped so tiny inputs don't make the model vanish
+            Model::Linearithmic => n * n.log2().max(1.),
+            Model::Quadratic => n * n,
+        }
+    }
+
+    /// The squared error in log space of the best fitting `c * model(n)`
+    fn error(self, lengths: &[f64], times: &[f64]) -> f64 {
+        let log_ratios: Vec<f64> = lengths
+            .iter()
+            .zip(times)
+            .map(|(&n, &t)| t.ln() - self.eval(n).ln())
+            .collect();
+        // ln(c) is the mean log ratio, what's left over is how badly the shape fits
+        let log_c = mean(&log_ratios);
+        log_ratios.iter().map(|r| (r - log_c).powi(2)).sum()
+    }
+}
+
+/// The growth of one sort on one distribution across every benchmarked length
+#[derive(Debug, Clone)]
+pub struct Fit<'a> {
+    pub sort: &'a str,
+    pub distribution: &'a str,
+    pub element_type: &'a str,
+    pub cache: CacheMode,
+    /// The slope of log time against log length, 1 for linear and 2 for quadratic growth
+    pub exponent: f64,
+    pub model: Model,
+}
+
+impl Fit<'_> {
+    pub fn quadratic(&self) -> bool {
+        self.model == Model::Quadratic
+    }
+}
+
+/// Fits every series of the report that has at least two lengths
+pub fn fit(report: &BenchmarkReport) -> Vec<Fit<'_>> {
+    let mut fits = Vec::new();
+    for element_type in report.element_types() {
+        for cache in report.cache_modes() {
+            for (sort, records) in
+                report.by_sort(|r| r.element_type == element_type && r.cache == cache)
+            {
+                for distribution in report.distributions() {
+                    let (lengths, times): (Vec<f64>, Vec<f64>) = records
+                        .iter()
+                        .filter(|r| r.distribution == distribution && r.length > 0)
+                        .map(|r| (r.length as f64, r.result.time.as_nanos().max(1) as f64))
+                        .unzip();
+                    if lengths.len() < 2 {
+                        continue;
+                    }
+                    let log_lengths: Vec<f64> = lengths.iter().map(|n| n.ln()).collect();
+                    let log_times: Vec<f64> = times.iter().map(|t| t.ln()).collect();
+                    let model = MODELS
+                        .into_iter()
+                        .min_by(|a, b| {
+                            a.error(&lengths, &times)
+                                .total_cmp(&b.error(&lengths, &times))
+                        })
+                        .expect("there are models");
+                    fits.push(Fit {
+                        sort,
+                        distribution,
+                        element_type,
+                        cache,
+                        exponent: slope(&log_lengths, &log_times),
+                        model,
+                    });
+                }
+            }
+        }
+    }
+    fits
+}
+
+pub fn print_fits(fits: &[Fit]) {
+    println!("Estimated complexity:");
+    let sort_width = fits.iter().map(|f| f.sort.len()).max().unwrap_or(0);
+    let dist_width = fits.iter().map(|f| f.distribution.len()).max().unwrap_or(0);
+    for f in fits {
+        let verdict = if f.quadratic() { "QUADRATIC" } else { "" };
+        let line = format!(
+            "    {:sort_width$}  {:dist_width$}  {} {}  n^{:<5.2}  {:<8}{verdict}",
+            f.sort,
+            f.distribution,
+            f.element_type,
+            f.cache.name(),
+            f.exponent,
+            f.model.name(),
+        );
+        println!("{}", line.trim_end());
+    }
+}
@@ -54,22 +54,36 @@ impl<T: num::PrimInt + AddAssign + RemAssign, const N: usize> InputDistribution<
     for ShuffledValues<N>
 {
     fn name() -> String {
-        if N == 0 {
-            return "Shuffled".to_string();
-        }
-        format!("Shuffled ({N} values)")
+        shuffled_values_name(N)
     }
     fn generate(rng: &mut impl Rng, len: usize) -> Vec<T> {
-        let mut v = Sorted::generate(rng, len);
-        if N != 0 {
-            let m = (0..N).fold(T::zero(), |acc, _| acc + T::one());
-            v.iter_mut().for_each(|x| *x %= m);
-        }
-        v.shuffle(rng);
-        v
+        // More values than `T` can hold leaves every value unique anyway
+        shuffled_values(rng, len, T::from(N).unwrap_or_else(T::zero))
     }
 }
 
+/// The name of [ShuffledValues] with `values` distinct values, for when it is only known at runtime
+pub fn shuffled_values_name(values: usize) -> String {
+    if values == 0 {
+        return "Shuffled".to_string();
+    }
+    format!("Shuffled ({values} values)")
+}
+
+/// The runtime equivalent of [ShuffledValues], 0 distinct values means every value is unique
+pub fn shuffled_values<T: num::PrimInt + AddAssign + RemAssign>(
+    rng: &mut impl Rng,
+    len: usize,
+    values: T,
+) -> Vec<T> {
+    let mut v = Sorted::generate(rng, len);
+    if !values.is_zero() {
+        v.iter_mut().for_each(|x| *x %= values);
+    }
+    v.shuffle(rng);
+    v
+}
+
 pub struct AscendingDescending;
 impl<T: num::PrimInt + AddAssign + SubAssign> InputDistribution<T> for AscendingDescending {
     fn name() -> String {
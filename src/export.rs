@@ -0,0 +1,163 @@
+use std::{fs::File, io::Write, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::alloc::MemoryStats;
+use crate::harness::BenchmarkResult;
+use crate::harness::CacheMode;
+use crate::perf::PerfCounters;
+use crate::report::{BenchmarkRecord, BenchmarkReport};
+use crate::system::RunMetadata;
+
+/// Everything recorded about a single run of a benchmark, as written to `results.json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Export {
+    pub suite: Option<String>,
+    pub seed: u64,
+    pub runs: usize,
+    #[serde(default)]
+    pub metadata: Option<RunMetadata>,
+    pub records: Vec<Record>,
+}
+
+/// One sort applied to one input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub sort: String,
+    pub stable: bool,
+    pub distribution: String,
+    pub length: usize,
+    pub element_type: String,
+    #[serde(default)]
+    pub cache: CacheMode,
+    pub success: bool,
+    pub mean_ns: u64,
+    pub samples_ns: Vec<u64>,
+    /// Empty if the timer doesn't count cycles
+    #[serde(default)]
+    pub samples_cycles: Vec<u64>,
+    /// Empty if performance counters weren't collected
+    #[serde(default)]
+    pub samples_counters: Vec<PerfCounters>,
+    #[serde(default)]
+    pub samples_memory: Vec<MemoryStats>,
+}
+
+pub fn records(report: &BenchmarkReport) -> Vec<Record> {
+    report
+        .iter()
+        .map(|r| Record {
+            sort: r.sort.clone(),
+            stable: r.stable,
+            distribution: r.distribution.clone(),
+            length: r.length,
+            element_type: r.element_type.clone(),
+            cache: r.cache,
+            success: r.result.success,
+            mean_ns: r.result.time.as_nanos() as u64,
+            samples_ns: r
+                .result
+                .samples
+                .iter()
+                .map(|s| s.as_nanos() as u64)
+                .collect(),
+            samples_cycles: r.result.cycles.clone(),
+            samples_counters: r.result.counters.clone(),
+            samples_memory: r.result.memory.clone(),
+        })
+        .collect()
+}
+
+impl From<Record> for BenchmarkRecord {
+    fn from(record: Record) -> Self {
+        BenchmarkRecord {
+            sort: record.sort,
+            stable: record.stable,
+            distribution: record.distribution,
+            length: record.length,
+            element_type: record.element_type,
+            cache: record.cache,
+            result: BenchmarkResult {
+                time: Duration::from_nanos(record.mean_ns),
+                samples: record
+                    .samples_ns
+                    .into_iter()
+                    .map(Duration::from_nanos)
+                    .collect(),
+                cycles: record.samples_cycles,
+                counters: record.samples_counters,
+                memory: record.samples_memory,
+                success: record.success,
+            },
+        }
+    }
+}
+
+pub fn write_json(path: &Path, export: &Export) -> std::io::Result<()> {
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(file, export)?;
+    Ok(())
+}
+
+pub fn read_json(path: &Path) -> std::io::Result<Export> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+/// Writes one row per sample, so the file can be loaded as a tidy table. `cycles` come from the
+/// timer, `perf_cycles` from the performance counters the IPC is computed with.
+pub fn write_csv(path: &Path, records: &[Record]) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(File::create(path)?);
+    writeln!(
+        file,
+        "sort,stable,distribution,length,element_type,cache,success,sample,time_ns,cycles,\
+        instructions,perf_cycles,branch_misses,cache_misses,ipc,task_clock_ns,page_faults,\
+        context_switches,peak_bytes,allocations,bytes_allocated"
+    )?;
+    for record in records {
+        for (sample, time) in record.samples_ns.iter().enumerate() {
+            let cycles = optional(record.samples_cycles.get(sample).copied());
+            let counters = record
+                .samples_counters
+                .get(sample)
+                .copied()
+                .unwrap_or_default();
+            let memory = record.samples_memory.get(sample);
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{},{sample},{time},{cycles},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&record.sort),
+                record.stable,
+                csv_field(&record.distribution),
+                record.length,
+                record.element_type,
+                record.cache.name(),
+                record.success,
+                optional(counters.instructions),
+                optional(counters.cycles),
+                optional(counters.branch_misses),
+                optional(counters.cache_misses),
+                optional(counters.ipc()),
+                optional(counters.task_clock_ns),
+                optional(counters.page_faults),
+                optional(counters.context_switches),
+                optional(memory.map(|m| m.peak_bytes)),
+                optional(memory.map(|m| m.allocations)),
+                optional(memory.map(|m| m.bytes_allocated)),
+            )?;
+        }
+    }
+    file.flush()
+}
+
+/// Missing values are left empty
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn csv_field(field: &str) -> String {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return field.to_string();
+    }
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
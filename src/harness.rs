@@ -1,40 +1,49 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use crate::sorts::SortingMethod;
+use crate::alloc::{self, MemoryStats};
+use crate::buffer::AlignedBuffer;
+use crate::cache::{self, CacheFlusher};
+use crate::perf::{PerfCounters, PerfGroup};
+use crate::registry::{DistributionEntry, Registry, SortEntry};
+use crate::report::{BenchmarkRecord, BenchmarkReport};
+use crate::timer::Timer;
 
-use super::distributions::*;
-use fxhash::FxHashMap;
-use rand::Rng;
+use clap::ValueEnum;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-pub trait HarnessInputProvider<T> {
-    fn generate_input(rng: &mut impl Rng, lengths: &[usize]) -> Vec<DistributionResult<T>>;
+/// The state of the CPU caches when a run starts
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheMode {
+    /// The input is read right before each run, so as much of it as fits is cached
+    #[default]
+    Warm,
+    /// The caches are thrashed with a large buffer before each run, so the input has to come
+    /// from memory
+    Cold,
 }
 
-macro_rules! harness_input_provider {
-    ($($t:ident),*) => {
-        impl<T,  $($t: InputDistribution<T>),*> HarnessInputProvider<T>
-            for ($($t),*,)
-        {
-            fn generate_input(
-                rng: &mut impl Rng,
-                lengths: &[usize],
-            ) -> Vec<DistributionResult<T>> {
-                let mut v = Vec::new();
-                $(
-                v.extend(
-                    lengths
-                        .into_iter()
-                        .map(|&len| <$t>::generate(rng, len))
-                        .map(|data| DistributionResult {
-                            name: <$t>::name(),
-                            data,
-                        }),
-                );
-                )*
-                v
-            }
+impl CacheMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            CacheMode::Warm => "warm",
+            CacheMode::Cold => "cold",
         }
-    };
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -43,106 +52,171 @@ pub struct DistributionResult<T> {
     data: Vec<T>,
 }
 
-harness_input_provider!(D1);
-harness_input_provider!(D1, D2);
-harness_input_provider!(D1, D2, D3);
-harness_input_provider!(D1, D2, D3, D4);
-harness_input_provider!(D1, D2, D3, D4, D5);
-harness_input_provider!(D1, D2, D3, D4, D5, D6);
-harness_input_provider!(D1, D2, D3, D4, D5, D6, D7);
-harness_input_provider!(D1, D2, D3, D4, D5, D6, D7, D8);
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct BenchmarkResult {
+    /// The mean of all samples
     pub time: Duration,
+    /// The time taken by each individual run
+    pub samples: Vec<Duration>,
+    /// The cycles taken by each individual run, empty if the timer doesn't count cycles
+    pub cycles: Vec<u64>,
+    /// Performance counters for each individual run, empty if they weren't collected
+    pub counters: Vec<PerfCounters>,
+    /// Heap usage of each individual run
+    pub memory: Vec<MemoryStats>,
     pub success: bool,
 }
 
+impl BenchmarkResult {
+    pub fn mean_cycles(&self) -> Option<f64> {
+        if self.cycles.is_empty() {
+            return None;
+        }
+        Some(self.cycles.iter().sum::<u64>() as f64 / self.cycles.len() as f64)
+    }
+    pub fn mean_counters(&self) -> Option<PerfCounters> {
+        PerfCounters::mean(&self.counters)
+    }
+    pub fn mean_memory(&self, get: impl Fn(&MemoryStats) -> usize) -> f64 {
+        if self.memory.is_empty() {
+            return 0.;
+        }
+        self.memory.iter().map(get).sum::<usize>() as f64 / self.memory.len() as f64
+    }
+}
+
+/// How the harness runs each case
 #[derive(Debug, Clone)]
-pub struct BenchmarkData {
-    pub result: BenchmarkResult,
-    pub name: String,
-    pub stable: bool,
+pub struct BenchOptions {
+    /// Every input is generated from a seed derived from this one
+    pub seed: u64,
+    pub lengths: Vec<usize>,
+    /// How many times each input is sorted
+    pub runs: usize,
+    pub timer: Timer,
+    /// Whether to collect performance counters for every run
+    pub counters: bool,
+    /// Every input is benchmarked once in each of these modes
+    pub cache: Vec<CacheMode>,
+    /// The alignment of the buffer inputs are sorted in, in bytes
+    pub alignment: usize,
 }
 
-pub trait SortingMethodProvider<T> {
-    fn run_all(data: &[DistributionResult<T>], runs: usize) -> Vec<BenchmarkData>;
+/// The seed for a single input, so that it doesn't depend on which other inputs are generated
+/// alongside it
+fn input_seed(seed: u64, distribution: &str, len: usize) -> u64 {
+    // FNV-1a, which unlike the std hashers is guaranteed to stay the same across releases
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for byte in seed
+        .to_le_bytes()
+        .into_iter()
+        .chain(distribution.bytes())
+        .chain(len.to_le_bytes())
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
 }
 
-macro_rules! sorting_provider {
-    ($($t:ident),*) => {
-        impl<T: PartialOrd + Clone, $($t: SortingMethod<T>),*> SortingMethodProvider<T> for ($($t),* ,) {
-            fn run_all(data: &[DistributionResult<T>], runs: usize) -> Vec<BenchmarkData> {
-                let mut v = Vec::with_capacity(data.len());
-                $(
-                v.extend(data.iter().map(|DistributionResult { name: _, data }| {
-                    let mut inputs: Vec<Vec<T>> = (0..runs).map(|_| data.to_vec()).collect();
-                    let start = Instant::now();
-                    for input in &mut inputs {
-                        <$t>::sort(input)
-                    }
-                    let time = start.elapsed().div_f64(runs as f64);
-                    let success = inputs
-                        .into_iter()
-                        .all(|v| v.windows(2).all(|s| s[0] <= s[1]));
-                    let result = BenchmarkResult { time, success };
-                    BenchmarkData {
-                        result,
-                        name: <$t>::name(),
-                        stable: <$t>::stable(),
-                    }
-                }));)*
-                v
+fn generate_inputs<T>(
+    seed: u64,
+    distributions: &[DistributionEntry<T>],
+    lengths: &[usize],
+) -> Vec<DistributionResult<T>> {
+    let mut v = Vec::with_capacity(distributions.len() * lengths.len());
+    for distribution in distributions {
+        v.extend(lengths.iter().map(|&len| {
+            let mut rng = StdRng::seed_from_u64(input_seed(seed, distribution.name(), len));
+            DistributionResult {
+                name: distribution.name().to_string(),
+                data: distribution.generate(&mut rng, len),
             }
-        }
-    };
+        }));
+    }
+    v
 }
 
-sorting_provider!(S1);
-sorting_provider!(S1, S2);
-sorting_provider!(S1, S2, S3);
-sorting_provider!(S1, S2, S3, S4);
-sorting_provider!(S1, S2, S3, S4, S5);
-sorting_provider!(S1, S2, S3, S4, S5, S6);
-sorting_provider!(S1, S2, S3, S4, S5, S6, S7);
-
-pub type HashMap<K, V> = FxHashMap<K, V>;
+fn run_all<T: PartialOrd + Clone>(
+    sorts: &[SortEntry<T>],
+    inputs: &[DistributionResult<T>],
+    options: &BenchOptions,
+    counters: Option<&PerfGroup>,
+) -> Vec<BenchmarkRecord> {
+    let mut v = Vec::with_capacity(options.cache.len() * sorts.len() * inputs.len());
+    let mut flusher = options
+        .cache
+        .contains(&CacheMode::Cold)
+        .then(CacheFlusher::default);
+    let longest = inputs.iter().map(|i| i.data.len()).max().unwrap_or(0);
+    let mut buffer = AlignedBuffer::new(longest, options.alignment);
+    for (&mode, sort) in options
+        .cache
+        .iter()
+        .flat_map(|mode| sorts.iter().map(move |sort| (mode, sort)))
+    {
+        v.extend(inputs.iter().map(|DistributionResult { name, data }| {
+            let runs = options.runs;
+            let mut measurements = Vec::with_capacity(runs);
+            let mut run_counters = Vec::with_capacity(runs);
+            let mut memory = Vec::with_capacity(runs);
+            let mut success = true;
+            for _ in 0..runs {
+                // Refilling the buffer isn't timed
+                let input = buffer.fill(data);
+                match (mode, flusher.as_mut()) {
+                    (CacheMode::Cold, Some(flusher)) => flusher.flush(),
+                    _ => cache::warm(input),
+                }
+                let baseline = alloc::reset();
+                let (measurement, values) = match counters {
+                    Some(group) => {
+                        let (measurement, values) =
+                            group.measure(|| options.timer.measure(|| sort.sort(input)));
+                        (measurement, Some(values))
+                    }
+                    None => (options.timer.measure(|| sort.sort(input)), None),
+                };
+                // Anything allocated from here on isn't the sort's doing
+                memory.push(alloc::since(baseline));
+                run_counters.extend(values);
+                measurements.push(measurement);
+                success &= input.windows(2).all(|s| s[0] <= s[1]);
+            }
+            let samples: Vec<Duration> = measurements.iter().map(|m| m.time).collect();
+            let cycles: Vec<u64> = measurements.iter().filter_map(|m| m.cycles).collect();
+            let time = samples.iter().sum::<Duration>().div_f64(runs as f64);
+            BenchmarkRecord {
+                sort: sort.name().to_string(),
+                stable: sort.stable(),
+                distribution: name.clone(),
+                length: data.len(),
+                element_type: std::any::type_name::<T>().to_string(),
+                cache: mode,
+                result: BenchmarkResult {
+                    time,
+                    samples,
+                    cycles,
+                    counters: run_counters,
+                    memory,
+                    success,
+                },
+            }
+        }));
+    }
+    v
+}
 
-/// The results are presented in this format:
-/// Two hashmaps, the first one containing unstable sorting algorithms, the second - stable
-///
-/// The first key of each hashmap is the number of elements
-/// The second key is the name of the sorting algorithm
-/// The values are the name of the input distribution and its runtime
-pub fn bench<T: std::fmt::Debug, D: HarnessInputProvider<T>, S: SortingMethodProvider<T>>(
-    rng: &mut impl Rng,
-    lengths: &[usize],
-    runs: usize,
-) -> [HashMap<usize, HashMap<String, Vec<(String, BenchmarkResult)>>>; 2] {
-    let inputs = D::generate_input(rng, lengths);
-    let results = S::run_all(&inputs, runs);
-    let mut maps: [HashMap<usize, HashMap<String, Vec<(String, BenchmarkResult)>>>; 2] =
-        std::array::from_fn(|_| HashMap::default());
-    inputs.iter().cycle().zip(results.into_iter()).for_each(
-        |(
-            DistributionResult {
-                name: distribution_name,
-                data,
-            },
-            BenchmarkData {
-                result,
-                name: sort_name,
-                stable,
-            },
-        )| {
-            let map = if stable { &mut maps[1] } else { &mut maps[0] };
-            let size = data.len();
-            let size_map: &mut HashMap<String, _> = map.entry(size).or_default();
-            size_map
-                .entry(sort_name)
-                .or_default()
-                .push((distribution_name.to_string(), result));
-        },
-    );
-    maps
+pub fn bench<T: PartialOrd + Clone>(
+    registry: &Registry<T>,
+    options: &BenchOptions,
+) -> BenchmarkReport {
+    let inputs = generate_inputs(options.seed, registry.distributions(), &options.lengths);
+    let counters = options.counters.then(PerfGroup::open);
+    BenchmarkReport::new(run_all(
+        registry.sorts(),
+        &inputs,
+        options,
+        counters.as_ref(),
+    ))
 }
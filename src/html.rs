@@ -0,0 +1,245 @@
+use std::{fs::File, io::Write, path::Path};
+
+use crate::report::{BenchmarkRecord, BenchmarkReport, CaseKey, Metric};
+use crate::system::RunMetadata;
+
+/// Everything shown in `report.html`
+pub struct Page<'a> {
+    pub suite: Option<&'a str>,
+    pub seed: u64,
+    pub runs: usize,
+    pub metadata: &'a RunMetadata,
+    pub metric: Metric,
+    /// One per element type, in the order they were benchmarked
+    pub sections: &'a [Section],
+}
+
+/// The results and charts of one element type
+pub struct Section {
+    pub report: BenchmarkReport,
+    /// The name and SVG markup of every chart, embedded as is
+    pub charts: Vec<(String, String)>,
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 1400px; color: #1e1e2f; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.7em; }
+td { text-align: right; font-variant-numeric: tabular-nums; }
+th[scope=row] { text-align: left; }
+td.best { background: #a6e3a1; font-weight: bold; }
+td.failure, li.failure { background: #f38ba8; }
+figure { margin: 0 0 2em; }
+figure svg { max-width: 100%; height: auto; }
+";
+
+/// Writes a single self-contained page with a section per element type, with the charts inlined
+/// rather than linked
+pub fn write_html(path: &Path, page: &Page) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(File::create(path)?);
+    let title = match page.suite {
+        Some(name) => format!("Sorting benchmark: {}", escape(name)),
+        None => "Sorting benchmark".to_string(),
+    };
+    writeln!(
+        file,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+        <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>"
+    )?;
+    write_metadata(&mut file, page)?;
+    for section in page.sections {
+        let element_types = section.report.element_types().join(", ");
+        writeln!(
+            file,
+            "<section id=\"{0}\">\n<h2>{0} elements</h2>",
+            escape(&element_types)
+        )?;
+        write_failures(&mut file, &section.report)?;
+        write_tables(&mut file, &section.report, page.metric)?;
+        if !section.charts.is_empty() {
+            writeln!(file, "<h3>Charts</h3>")?;
+        }
+        for (name, svg) in &section.charts {
+            writeln!(
+                file,
+                "<figure id=\"{}-{}\">\n{}\n</figure>",
+                escape(&element_types),
+                escape(name),
+                strip_xml_declaration(svg)
+            )?;
+        }
+        writeln!(file, "</section>")?;
+    }
+    writeln!(file, "</body>\n</html>")?;
+    file.flush()
+}
+
+fn write_metadata(file: &mut impl Write, page: &Page) -> std::io::Result<()> {
+    let m = page.metadata;
+    let rows = [
+        (
+            "Element types",
+            page.sections
+                .iter()
+                .flat_map(|s| s.report.element_types())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        ("Seed", page.seed.to_string()),
+        ("Runs per case", page.runs.to_string()),
+        ("Timer", m.timer.clone()),
+        ("CPU", optional(m.cpu_model.as_ref())),
+        ("Cores", optional(m.cpu_count)),
+        ("Pinned to core", optional(m.pinned_core)),
+        ("Niceness", optional(m.nice)),
+        ("Frequency governor", optional(m.governor.as_ref())),
+        (
+            "Frequency",
+            optional(m.frequency_mhz.map(|f| format!("{f} MHz"))),
+        ),
+        (
+            "Last level cache",
+            optional(m.last_level_cache_bytes.map(|b| format!("{} KiB", b >> 10))),
+        ),
+        (
+            "Load average",
+            optional(
+                m.load_average
+                    .map(|[one, five, fifteen]| format!("{one:.2} {five:.2} {fifteen:.2}")),
+            ),
+        ),
+    ];
+    writeln!(file, "<h2>Run</h2>\n<table>")?;
+    for (name, value) in rows {
+        writeln!(
+            file,
+            "<tr><th scope=\"row\">{name}</th><td>{}</td></tr>",
+            escape(&value)
+        )?;
+    }
+    writeln!(file, "</table>")?;
+    let warnings = m.warnings();
+    if !warnings.is_empty() {
+        writeln!(file, "<ul>")?;
+        for warning in warnings {
+            writeln!(file, "<li>Warning: {}</li>", escape(&warning))?;
+        }
+        writeln!(file, "</ul>")?;
+    }
+    Ok(())
+}
+
+fn write_failures(file: &mut impl Write, report: &BenchmarkReport) -> std::io::Result<()> {
+    let failures: Vec<&BenchmarkRecord> = report.failures().collect();
+    if failures.is_empty() {
+        return Ok(());
+    }
+    writeln!(file, "<h3>Verification failures</h3>\n<ul>")?;
+    for record in failures {
+        writeln!(
+            file,
+            "<li class=\"failure\">{} failed to sort {} elements of {} with a {} cache</li>",
+            escape(&record.sort),
+            record.length,
+            escape(&record.distribution),
+            record.cache.name()
+        )?;
+    }
+    writeln!(file, "</ul>")
+}
+
+/// One table per element type, cache mode and length, with a row per distribution and a column per
+/// sort. The lowest value of each row that sorted correctly is marked as the best.
+fn write_tables(
+    file: &mut impl Write,
+    report: &BenchmarkReport,
+    metric: Metric,
+) -> std::io::Result<()> {
+    let sorts = report.sorts();
+    let cache_modes = report.cache_modes();
+    let element_types = report.element_types();
+    writeln!(file, "<h3>Results</h3>")?;
+    for &element_type in &element_types {
+        for &cache in &cache_modes {
+            for length in report.lengths() {
+                // Sections are per element type already, so it only needs naming when they're mixed
+                let element_type_name = if element_types.len() > 1 {
+                    format!(" {}", escape(element_type))
+                } else {
+                    String::new()
+                };
+                let regime = if cache_modes.len() > 1 {
+                    format!(", {} cache", cache.name())
+                } else {
+                    String::new()
+                };
+                writeln!(
+                file,
+                "<h4>{length}{element_type_name} elements{regime}</h4>\n<table>\n<tr><th>{}</th>",
+                escape(metric.label())
+            )?;
+                for sort in &sorts {
+                    write!(file, "<th scope=\"col\">{}</th>", escape(sort))?;
+                }
+                writeln!(file, "</tr>")?;
+                for distribution in report.distributions() {
+                    let cells: Vec<Option<&BenchmarkRecord>> = sorts
+                        .iter()
+                        .map(|&sort| {
+                            report.get(CaseKey {
+                                sort,
+                                distribution,
+                                length,
+                                element_type,
+                                cache,
+                            })
+                        })
+                        .collect();
+                    let best = cells
+                        .iter()
+                        .flatten()
+                        .filter(|r| r.result.success)
+                        .map(|r| r.metric(metric))
+                        .fold(f64::INFINITY, f64::min);
+                    write!(file, "<tr><th scope=\"row\">{}</th>", escape(distribution))?;
+                    for cell in cells {
+                        match cell {
+                            None => write!(file, "<td></td>")?,
+                            Some(r) if !r.result.success => {
+                                write!(file, "<td class=\"failure\" title=\"Not sorted\">✗</td>")?
+                            }
+                            Some(r) => {
+                                let value = r.metric(metric);
+                                let class = if value == best { " class=\"best\"" } else { "" };
+                                write!(file, "<td{class}>{value:.2}</td>")?
+                            }
+                        }
+                    }
+                    writeln!(file, "</tr>")?;
+                }
+                writeln!(file, "</table>")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Missing values are shown as a dash
+fn optional(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "–".to_string(), |v| v.to_string())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// An XML declaration is only valid at the start of a document, not inside HTML
+fn strip_xml_declaration(svg: &str) -> &str {
+    match svg.trim_start().strip_prefix("<?xml") {
+        Some(rest) => rest.split_once("?>").map_or(svg, |(_, rest)| rest),
+        None => svg,
+    }
+}
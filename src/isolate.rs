@@ -0,0 +1,143 @@
+use std::{
+    ops::{AddAssign, RemAssign, SubAssign},
+    process::{Command, Stdio},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::export::{self, Record};
+use crate::harness::bench;
+use crate::registry::Registry;
+use crate::report::{BenchmarkRecord, BenchmarkReport};
+use crate::suite::{ElementType, Suite};
+use crate::timer::Timer;
+
+/// The argument that makes the binary run a single job read from stdin
+pub const WORKER_ARG: &str = "--worker";
+
+/// Which benchmark cases get a process of their own
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Isolation {
+    /// Run everything in this process
+    #[default]
+    None,
+    /// Run each sorting method in a separate process
+    Sort,
+    /// Run each combination of sorting method, input distribution and length in a separate
+    /// process
+    Case,
+}
+
+/// What a worker process runs, sent to it as JSON over stdin
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub suite: Suite,
+    pub element_type: ElementType,
+    pub seed: u64,
+    pub timer: Timer,
+    pub sort: String,
+    /// Every distribution in the suite if `None`
+    pub distribution: Option<String>,
+    /// Every length in the suite if `None`
+    pub length: Option<usize>,
+}
+
+/// Runs every sort of the registry in a fresh process, or every case if `suite.isolation` is
+/// [Isolation::Case]
+pub fn run_isolated<T>(
+    suite: &Suite,
+    registry: &Registry<T>,
+    element_type: ElementType,
+    seed: u64,
+    timer: Timer,
+) -> Result<BenchmarkReport, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("failed to find the sort_bench executable: {e}"))?;
+    let job = |sort: &str, distribution: Option<&str>, length| Job {
+        suite: suite.clone(),
+        element_type,
+        seed,
+        timer,
+        sort: sort.to_string(),
+        distribution: distribution.map(str::to_string),
+        length,
+    };
+    let mut jobs = Vec::new();
+    for sort in registry.sorts() {
+        match suite.isolation {
+            Isolation::None | Isolation::Sort => jobs.push(job(sort.name(), None, None)),
+            Isolation::Case => {
+                for distribution in registry.distributions() {
+                    for &length in &suite.lengths {
+                        jobs.push(job(sort.name(), Some(distribution.name()), Some(length)));
+                    }
+                }
+            }
+        }
+    }
+    let mut report = BenchmarkReport::default();
+    for job in &jobs {
+        report.extend(spawn(&exe, job)?);
+    }
+    Ok(report)
+}
+
+fn spawn(exe: &std::path::Path, job: &Job) -> Result<Vec<BenchmarkRecord>, String> {
+    let describe = || match (&job.distribution, job.length) {
+        (Some(distribution), Some(length)) => format!("{} on {length} {distribution}", job.sort),
+        _ => job.sort.clone(),
+    };
+    let mut child = Command::new(exe)
+        .arg(WORKER_ARG)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("failed to start a worker for {}: {e}", describe()))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    serde_json::to_writer(&mut stdin, job)
+        .map_err(|e| format!("failed to send {} to its worker: {e}", describe()))?;
+    // Closes stdin, letting the worker know the whole job was sent
+    drop(stdin);
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed to wait for the worker for {}: {e}", describe()))?;
+    if !output.status.success() {
+        return Err(format!(
+            "the worker for {} exited with {}",
+            describe(),
+            output.status
+        ));
+    }
+    let records: Vec<Record> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("invalid results from the worker for {}: {e}", describe()))?;
+    Ok(records.into_iter().map(BenchmarkRecord::from).collect())
+}
+
+pub fn read_job() -> Result<Job, String> {
+    serde_json::from_reader(std::io::stdin().lock()).map_err(|e| format!("invalid job: {e}"))
+}
+
+/// Runs a job inside the worker process, writing the results to stdout
+pub fn run_job<T>(job: &Job) -> Result<(), String>
+where
+    T: num::PrimInt + AddAssign + SubAssign + RemAssign + 'static,
+    rand::distributions::Standard: rand::distributions::Distribution<T>,
+{
+    // The parent already reported anything that failed here
+    crate::system::prepare(job.suite.pin_core, job.suite.raise_priority);
+    let mut registry = job.suite.registry::<T>()?;
+    registry.retain_sorts(|s| s.name() == job.sort);
+    if let Some(distribution) = &job.distribution {
+        registry.retain_distributions(|d| d.name() == distribution);
+    }
+    let mut options = job.suite.options(job.seed, job.timer);
+    if let Some(length) = job.length {
+        options.lengths = vec![length];
+    }
+    let report = bench(&registry, &options);
+    serde_json::to_writer(std::io::stdout().lock(), &export::records(&report))
+        .map_err(|e| format!("failed to write results: {e}"))
+}
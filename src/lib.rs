@@ -0,0 +1,24 @@
+//! Benchmarks sorting algorithms over a range of input distributions, see `main.rs` for the
+//! command line tool built on top of it
+pub mod alloc;
+pub mod baseline;
+pub mod buffer;
+pub mod cache;
+pub mod cli;
+pub mod complexity;
+pub mod distributions;
+pub mod export;
+pub mod harness;
+pub mod html;
+pub mod isolate;
+pub mod markdown;
+pub mod perf;
+pub mod plot;
+pub mod registry;
+pub mod report;
+pub mod sorts;
+pub mod stats;
+pub mod suite;
+pub mod system;
+pub mod terminal;
+pub mod timer;
@@ -1,214 +1,276 @@
-mod distributions;
-mod harness;
-mod sorts;
-use distributions::*;
-use harness::*;
-use plotters::prelude::*;
-use sorts::*;
+use std::{
+    ops::{AddAssign, RemAssign, SubAssign},
+    path::Path,
+};
 
-struct Colors;
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use rand::Rng;
+use sort_bench::cli::*;
+use sort_bench::harness::*;
+use sort_bench::isolate::Isolation;
+use sort_bench::registry::*;
+use sort_bench::report::*;
+use sort_bench::suite::*;
+use sort_bench::timer::Timer;
+use sort_bench::{
+    alloc, baseline, complexity, export, html, isolate, markdown, perf, plot, stats, system,
+    terminal,
+};
 
-macro_rules! colors {
-    ($($col:expr),*) => {
-        [$({let [r, g, b] = color_hex::color_from_hex!($col); (r,g,b) }),*]
-    };
-}
+#[global_allocator]
+static ALLOCATOR: alloc::TrackingAllocator = alloc::TrackingAllocator;
 
-impl Palette for Colors {
-    /// 0..16 are the base16 colors
-    /// 16..18 are the background, forground and cursor color
-    #[rustfmt::skip]
-    const COLORS: &'static [(u8, u8, u8)] = &colors!(
-        "#1E1E2F", "#F38BA8", "#A6E3A1", "#F9E2AF", "#89B4FA", "#F5C2E7", "#94E2D5", "#BAC2DE",
-        "#4e4e75", "#F38BA8", "#A6E3A1", "#F9E2AF", "#89B4FA", "#F5C2E7", "#94E2D5", "#A6ADC8",
-        "#1E1E2F", "#CDD6F4", "#8CAAEE",
-        "#5C5F77", "#D20F39", "#40A02B", "#DF8E1D", "#1E66F5", "#EA76CB", "#179299", "#ACB0BE",
-        "#6C6F85", "#D20F39", "#40A02B", "#DF8E1D", "#1E66F5", "#EA76CB", "#179299", "#BCC0CC",
-        "#EFF1F5", "#4C4F69", "#1E66F5"
-    );
+fn run<T>(
+    suite: &Suite,
+    element_type: ElementType,
+    seed: u64,
+    timer: Timer,
+) -> Result<BenchmarkReport, String>
+where
+    T: num::PrimInt + AddAssign + SubAssign + RemAssign + 'static,
+    rand::distributions::Standard: rand::distributions::Distribution<T>,
+{
+    let registry = suite.registry::<T>()?;
+    if suite.isolation != Isolation::None {
+        return isolate::run_isolated(suite, &registry, element_type, seed, timer);
+    }
+    Ok(bench(&registry, &suite.options(seed, timer)))
 }
 
 fn main() {
-    let mut rng = rand::thread_rng();
-    // let lengths = [100, 1_000, 10_000, 100_000, 1_000_000];
-    let lengths = [100, 1_000, 10_000];
-    let results = bench::<
-        i32, // The type we'll be sorting
-        // The input distributions
-        (
-            Uniform,
-            Sorted,
-            Reverse,
-            AllEqual,
-            Shuffled,
-            ShuffledValues<16>,
-        ),
-        (BubbleSort, InsertionSort), // Sorting methods
-    >(&mut rng, &lengths, 2);
-
-    let dark = true;
-    let get_color = |idx| Colors::pick(if dark { idx } else { idx + 18 });
-    // let background = color(plotters::style::Palette)
-    let font = |size| {
-        FontFamily::SansSerif
-            .into_font()
-            .resize(size)
-            .with_color(&get_color(17))
+    let args = Args::parse();
+    if args.worker {
+        let result = isolate::read_job().and_then(|job| match job.element_type {
+            ElementType::I32 => isolate::run_job::<i32>(&job),
+            ElementType::U32 => isolate::run_job::<u32>(&job),
+            ElementType::I64 => isolate::run_job::<i64>(&job),
+            ElementType::U64 => isolate::run_job::<u64>(&job),
+        });
+        if let Err(e) = result {
+            eprintln!("Worker failed: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.list {
+        let registry = Registry::<i32>::builtin();
+        println!("Sorting methods:");
+        for sort in registry.sorts() {
+            let stability = if sort.stable() { "stable" } else { "unstable" };
+            println!("    {} ({stability})", sort.name());
+        }
+        println!("Input distributions:");
+        for distribution in registry.distributions() {
+            println!("    {}", distribution.name());
+        }
+        return;
+    }
+    let suite = args
+        .suite()
+        .unwrap_or_else(|e| Args::command().error(ErrorKind::InvalidValue, e).exit());
+    if let Some(name) = &suite.name {
+        eprintln!("Running suite {name}");
+    }
+    let seed = suite.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    eprintln!("Using seed {seed}");
+    let (pinned_core, problems) = system::prepare(suite.pin_core, suite.raise_priority);
+    for problem in problems {
+        eprintln!("Warning: {problem}");
+    }
+    // Loaded before anything runs, so a missing baseline fails fast and --save-baseline with the
+    // same name can't replace it before the comparison
+    let base = args.baseline.as_ref().map(|name| {
+        baseline::load(&args.baseline_dir, name)
+            .unwrap_or_else(|e| fail("load", &baseline::path(&args.baseline_dir, name), e))
+    });
+    if base.is_some() {
+        match stats::runs_for_significance(args.significance) {
+            Some(needed) if needed <= suite.runs => {}
+            needed => eprintln!(
+                "Warning: {} runs are too few to reliably detect regressions at p < {}{}",
+                suite.runs,
+                args.significance,
+                needed.map_or(String::new(), |n| format!(", use --runs {n} or more"))
+            ),
+        }
+    }
+    let timer = Timer::detect();
+    eprintln!("Timing with {timer}");
+    let metadata = system::RunMetadata::collect(pinned_core, timer.to_string());
+    for warning in metadata.warnings() {
+        eprintln!("Warning: {warning}");
+    }
+    if suite.counters {
+        match perf::PerfGroup::open().availability() {
+            perf::Availability::Hardware => {}
+            perf::Availability::Software => {
+                eprintln!("Hardware performance counters are unavailable, using software counters")
+            }
+            perf::Availability::Unavailable => {
+                eprintln!("Performance counters are unavailable")
+            }
+        }
+    }
+    let metric = match (suite.metric, timer) {
+        (Metric::Cycles, Timer::Instant) => {
+            eprintln!("No cycle counter is available, plotting time per element instead");
+            Metric::TimePerElement
+        }
+        (metric, _) => metric,
     };
+    let chart_options = plot::ChartOptions {
+        charts: &suite.charts,
+        theme: suite.theme,
+        metric,
+        reference: suite.reference.as_deref(),
+        log_scale: suite.log_scale,
+    };
+    let mut records = Vec::new();
+    let mut html_sections = Vec::new();
+    for &element_type in &suite.element_types {
+        let report = match element_type {
+            ElementType::I32 => run::<i32>(&suite, element_type, seed, timer),
+            ElementType::U32 => run::<u32>(&suite, element_type, seed, timer),
+            ElementType::I64 => run::<i64>(&suite, element_type, seed, timer),
+            ElementType::U64 => run::<u64>(&suite, element_type, seed, timer),
+        }
+        .unwrap_or_else(|e| Args::command().error(ErrorKind::InvalidValue, e).exit());
+        for record in report.failures() {
+            eprintln!(
+                "{} failed to sort {} {} elements of {} with a {} cache",
+                record.sort,
+                record.length,
+                record.element_type,
+                record.distribution,
+                record.cache.name()
+            );
+        }
+        let fits = complexity::fit(&report);
+        if !fits.is_empty() {
+            complexity::print_fits(&fits);
+            for fit in fits.iter().filter(|f| f.quadratic()) {
+                eprintln!(
+                    "{} grows quadratically on {} with a {} cache",
+                    fit.sort,
+                    fit.distribution,
+                    fit.cache.name()
+                );
+            }
+        }
+        // Keep the results for each element type apart when there's more than one
+        let output = if suite.element_types.len() > 1 {
+            suite.output.join(element_type.name())
+        } else {
+            suite.output.clone()
+        };
+        std::fs::create_dir_all(&output).unwrap_or_else(|e| fail("create", &output, e));
+        let mut html_charts = None;
+        for format in &suite.formats {
+            match format {
+                OutputFormat::Png | OutputFormat::Svg => {
+                    let target = plot::Target {
+                        dir: output.clone(),
+                        format: if *format == OutputFormat::Svg {
+                            plot::ImageFormat::Svg
+                        } else {
+                            plot::ImageFormat::Png
+                        },
+                        resolution: suite.resolution,
+                    };
+                    plot::draw(&report, &chart_options, &target)
+                        .unwrap_or_else(|e| fail("draw the charts in", &output, e));
+                }
+                OutputFormat::Json => {
+                    let path = output.join("results.json");
+                    let export = export::Export {
+                        suite: suite.name.clone(),
+                        seed,
+                        runs: suite.runs,
+                        metadata: Some(metadata.clone()),
+                        records: export::records(&report),
+                    };
+                    export::write_json(&path, &export).unwrap_or_else(|e| fail("write", &path, e));
+                }
+                OutputFormat::Csv => {
+                    let path = output.join("results.csv");
+                    export::write_csv(&path, &export::records(&report))
+                        .unwrap_or_else(|e| fail("write", &path, e));
+                }
+                OutputFormat::Html => {
+                    let charts = plot::render_svg(&report, &chart_options, suite.resolution)
+                        .unwrap_or_else(|e| fail("draw the charts in", &output, e));
+                    html_charts = Some(charts);
+                }
+                OutputFormat::Table => terminal::print_tables(&report, metric, suite.bars),
+                OutputFormat::Markdown => {
+                    let path = output.join("results.md");
+                    markdown::write_markdown(&path, &report, metric, suite.reference.as_deref())
+                        .unwrap_or_else(|e| fail("write", &path, e));
+                }
+            }
+        }
+        records.extend(export::records(&report));
+        // The page covers every element type, so it's written once they have all run
+        if let Some(charts) = html_charts {
+            html_sections.push(html::Section { report, charts });
+        }
+    }
+    if !html_sections.is_empty() {
+        let path = suite.output.join("report.html");
+        let page = html::Page {
+            suite: suite.name.as_deref(),
+            seed,
+            runs: suite.runs,
+            metadata: &metadata,
+            metric,
+            sections: &html_sections,
+        };
+        html::write_html(&path, &page).unwrap_or_else(|e| fail("write", &path, e));
+    }
 
-    let [unstable, stable] = results;
-    for (stable, data) in [("unstable", unstable), ("stable", stable)] {
-        for (size, data) in data {
-            let path = format!("images/{stable}_{size}.png");
-            let root = BitMapBackend::new(&path, (1920, 1080)).into_drawing_area();
-
-            root.fill(&get_color(16)).unwrap();
-
-            // Boxplot::new_horizontal(key, quartiles)
-            let distr_count = data.values().map(|d| d.len()).max().unwrap_or(0) as i32;
-            let method_count = data.values().len() as i32;
-            let max_runtime = data
-                .values()
-                .map(|v| {
-                    v.iter()
-                        .map(|x| x.1.time.as_nanos() as i32)
-                        .max()
-                        .unwrap_or(0)
-                })
-                .max()
-                .unwrap_or(0);
-
-            const MARGIN: u32 = 15;
-            const BAR_STROKE: u32 = 2;
-            let bar_colors: [_; 6] = std::array::from_fn(|idx| get_color(1 + idx));
-            let (left, right) = root.split_horizontally((20).percent());
-            let mut chart = ChartBuilder::on(&right)
-                .caption(format!("Sorting {size} elements"), font(40.))
-                .margin_top(MARGIN)
-                .margin_right(MARGIN)
-                .margin_bottom(MARGIN)
-                .set_label_area_size(LabelAreaPosition::Bottom, 40)
-                .build_cartesian_2d(
-                    0..(max_runtime as f64 * 1.15) as i32,
-                    (0..(method_count + 1) * (distr_count)).into_segmented(),
-                )
-                .unwrap();
-            chart
-                .configure_mesh()
-                .disable_y_mesh()
-                .disable_y_axis()
-                .axis_style(get_color(17))
-                .bold_line_style(get_color(7))
-                .light_line_style(get_color(8))
-                .label_style(font(22.))
-                .x_label_formatter(&|&x| (x as f64 / size as f64).to_string())
-                .x_desc("Cycles per element")
-                .axis_desc_style(font(22.))
-                .draw()
-                .unwrap();
-            let text = ChartBuilder::on(&left)
-                .margin_left(MARGIN)
-                .margin_top(MARGIN)
-                .margin_bottom(MARGIN)
-                .set_label_area_size(LabelAreaPosition::Bottom, 40)
-                .build_cartesian_2d(
-                    0..1,
-                    (0..(method_count + 1) * (distr_count)).into_segmented(),
-                )
-                .unwrap();
-            let font_color = get_color(17);
-            data.iter()
-                .enumerate()
-                .map(|(i, sort)| (i as i32, sort))
-                .zip(bar_colors.iter().cycle())
-                .for_each(|((ytop, (sort, results)), color)| {
-                    chart
-                        .draw_series(
-                            (ytop + 1..)
-                                .step_by(method_count as usize + 1)
-                                .zip(results.into_iter())
-                                .flat_map(|(y, (dist, bench))| {
-                                    text.plotting_area()
-                                        .draw(&Text::new(
-                                            dist.to_string(),
-                                            (
-                                                0,
-                                                SegmentValue::Exact(
-                                                    (y - 1) / (method_count + 1)
-                                                        * (method_count + 1)
-                                                        + method_count / 2
-                                                        + method_count % 2
-                                                        + 1,
-                                                ),
-                                            ),
-                                            {
-                                                let mut style = font_color
-                                                    .into_text_style(text.plotting_area());
-                                                style.font = FontDesc::new(
-                                                    FontFamily::SansSerif,
-                                                    26.,
-                                                    FontStyle::Normal,
-                                                );
-                                                style
-                                            },
-                                        ))
-                                        .unwrap();
-                                    let coords = [
-                                        (0, SegmentValue::Exact(y)),
-                                        (bench.time.as_nanos() as i32, SegmentValue::Exact(y + 1)),
-                                    ];
-                                    [
-                                        Rectangle::new(coords.clone(), color.filled()),
-                                        Rectangle::new(
-                                            {
-                                                let mut c = coords;
-                                                let offset = BAR_STROKE as i32 / 2;
-                                                c[0].0 += offset;
-                                                c[1].0 += offset;
-                                                c
-                                            },
-                                            get_color(8).stroke_width(2),
-                                        ),
-                                    ]
-                                }),
-                        )
-                        .unwrap()
-                        .label(sort)
-                        .legend(move |(x, y)| {
-                            PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(5))
-                        });
-                });
-
-            text.plotting_area()
-                .draw(&Text::new(
-                    "Input distribution",
-                    (0, SegmentValue::Exact((method_count + 1) * (distr_count))),
-                    {
-                        let mut style = font_color.into_text_style(text.plotting_area());
-                        style.font = FontDesc::new(FontFamily::SansSerif, 26., FontStyle::Normal);
-                        style
-                    },
-                ))
-                .unwrap();
-            chart
-                .configure_series_labels()
-                .border_style(&get_color(8))
-                .background_style(&get_color(16).mix(0.8))
-                .label_font(
-                    FontFamily::SansSerif
-                        .into_font()
-                        .resize(30.)
-                        .with_color(get_color(17)),
-                )
-                .draw()
-                .unwrap();
-            // chart
-            //     .draw_series(LineSeries::new(
-            //         (-314..314).map(|x| x as f64 / 100.0).map(|x| (x, x.sin())),
-            //         &RED,
-            //     ))
-            //     .unwrap();
-            root.present().unwrap();
+    let export = export::Export {
+        suite: suite.name.clone(),
+        seed,
+        runs: suite.runs,
+        metadata: Some(metadata),
+        records,
+    };
+    if let Some(name) = &args.save_baseline {
+        let path = baseline::save(&args.baseline_dir, name, &export)
+            .unwrap_or_else(|e| fail("save", &baseline::path(&args.baseline_dir, name), e));
+        eprintln!("Saved baseline `{name}` to {}", path.display());
+    }
+    if let (Some(name), Some(base)) = (&args.baseline, base) {
+        let comparisons = baseline::compare(
+            &base.records,
+            &export.records,
+            args.regression_threshold / 100.,
+            args.significance,
+        );
+        baseline::print_comparisons(name, &comparisons, args.significance);
+        let untestable = comparisons.iter().filter(|c| c.untestable).count();
+        if untestable != 0 {
+            eprintln!(
+                "Warning: {untestable} cases had too few runs here or in the baseline to be tested \
+                for significance and were judged on --regression-threshold alone"
+            );
+        }
+        let missing = export.records.len() - comparisons.len();
+        if missing != 0 {
+            eprintln!("{missing} cases are not in the baseline and were not compared");
+        }
+        let regressed = comparisons.iter().filter(|c| c.regressed).count();
+        if regressed != 0 {
+            eprintln!(
+                "{regressed} cases regressed by more than {}%",
+                args.regression_threshold
+            );
+            std::process::exit(1);
         }
     }
 }
+
+fn fail(action: &str, path: &Path, error: impl std::fmt::Display) -> ! {
+    eprintln!("Failed to {action} {}: {error}", path.display());
+    std::process::exit(1)
+}
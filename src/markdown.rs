@@ -0,0 +1,109 @@
+use std::{fs::File, io::Write, path::Path};
+
+use crate::report::{BenchmarkRecord, BenchmarkReport, CaseKey, Metric};
+
+/// Writes a table per element type, cache mode and length, with a row per distribution and a column
+/// per sort, meant to be pasted into a pull request. Given a `reference` sort, every other cell also shows
+/// how many times faster than it the sort was.
+pub fn write_markdown(
+    path: &Path,
+    report: &BenchmarkReport,
+    metric: Metric,
+    reference: Option<&str>,
+) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(File::create(path)?);
+    let sorts = report.sorts();
+    let reference = reference.and_then(|want| {
+        sorts
+            .iter()
+            .copied()
+            .find(|sort| sort.eq_ignore_ascii_case(want))
+    });
+    let cache_modes = report.cache_modes();
+    for element_type in report.element_types() {
+        for &cache in &cache_modes {
+            for length in report.lengths() {
+                let regime = if cache_modes.len() > 1 {
+                    format!(" with a {} cache", cache.name())
+                } else {
+                    String::new()
+                };
+                writeln!(file, "### {length} {element_type} elements{regime}\n")?;
+                match reference {
+                    Some(reference) => writeln!(
+                        file,
+                        "{}, speedup over {reference} in brackets, best in bold\n",
+                        metric.label()
+                    )?,
+                    None => writeln!(file, "{}, best in bold\n", metric.label())?,
+                }
+                write!(file, "| Distribution |")?;
+                for sort in &sorts {
+                    write!(file, " {} |", cell(sort))?;
+                }
+                writeln!(file, "\n|:--|{}", "--:|".repeat(sorts.len()))?;
+                for distribution in report.distributions() {
+                    let find = |sort| {
+                        report.get(CaseKey {
+                            sort,
+                            distribution,
+                            length,
+                            element_type,
+                            cache,
+                        })
+                    };
+                    let cells: Vec<Option<&BenchmarkRecord>> =
+                        sorts.iter().map(|s| find(s)).collect();
+                    let best = cells
+                        .iter()
+                        .flatten()
+                        .filter(|r| r.result.success)
+                        .map(|r| r.metric(metric))
+                        .fold(f64::INFINITY, f64::min);
+                    let baseline = reference
+                        .and_then(find)
+                        .filter(|r| r.result.success)
+                        .map(|r| r.metric(metric));
+                    write!(file, "| {} |", cell(distribution))?;
+                    for record in cells {
+                        let Some(record) = record else {
+                            write!(file, " |")?;
+                            continue;
+                        };
+                        if !record.result.success {
+                            write!(file, " not sorted |")?;
+                            continue;
+                        }
+                        let value = record.metric(metric);
+                        let mut text = format!("{value:.2}");
+                        if value == best {
+                            text = format!("**{text}**");
+                        }
+                        match baseline {
+                            Some(baseline)
+                                if Some(record.sort.as_str()) != reference && value > 0. =>
+                            {
+                                write!(file, " {text} ({}×) |", speedup(baseline / value))?
+                            }
+                            _ => write!(file, " {text} |")?,
+                        }
+                    }
+                    writeln!(file)?;
+                }
+                writeln!(file)?;
+            }
+        }
+    }
+    file.flush()
+}
+
+/// Two decimals, or more for ratios so small they'd round to zero
+fn speedup(ratio: f64) -> String {
+    let decimals = (1. - ratio.log10().floor()).clamp(2., 6.) as usize;
+    format!("{ratio:.decimals$}")
+}
+
+/// Pipes would end the cell early
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+/// Counter values for a single run, missing counters are `None`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PerfCounters {
+    pub instructions: Option<u64>,
+    pub cycles: Option<u64>,
+    pub branch_misses: Option<u64>,
+    pub cache_misses: Option<u64>,
+    pub task_clock_ns: Option<u64>,
+    pub page_faults: Option<u64>,
+    pub context_switches: Option<u64>,
+}
+
+impl PerfCounters {
+    /// Instructions per cycle
+    pub fn ipc(&self) -> Option<f64> {
+        match (self.instructions, self.cycles) {
+            (Some(instructions), Some(cycles)) if cycles != 0 => {
+                Some(instructions as f64 / cycles as f64)
+            }
+            _ => None,
+        }
+    }
+
+    /// The per-run mean of every counter that was recorded in all of `runs`
+    pub fn mean(runs: &[PerfCounters]) -> Option<PerfCounters> {
+        if runs.is_empty() {
+            return None;
+        }
+        let mean = |get: fn(&PerfCounters) -> Option<u64>| {
+            let sum = runs.iter().map(get).sum::<Option<u64>>()?;
+            Some(sum / runs.len() as u64)
+        };
+        Some(PerfCounters {
+            instructions: mean(|c| c.instructions),
+            cycles: mean(|c| c.cycles),
+            branch_misses: mean(|c| c.branch_misses),
+            cache_misses: mean(|c| c.cache_misses),
+            task_clock_ns: mean(|c| c.task_clock_ns),
+            page_faults: mean(|c| c.page_faults),
+            context_switches: mean(|c| c.context_switches),
+        })
+    }
+}
+
+/// Which kind of counters could be opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    Hardware,
+    /// Hardware counters aren't exposed, which is common in VMs
+    Software,
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    Instructions,
+    Cycles,
+    BranchMisses,
+    CacheMisses,
+    TaskClock,
+    PageFaults,
+    ContextSwitches,
+}
+
+const HARDWARE_EVENTS: [Event; 4] = [
+    Event::Instructions,
+    Event::Cycles,
+    Event::BranchMisses,
+    Event::CacheMisses,
+];
+const SOFTWARE_EVENTS: [Event; 3] = [Event::TaskClock, Event::PageFaults, Event::ContextSwitches];
+
+impl Event {
+    fn store(self, counters: &mut PerfCounters, value: u64) {
+        let field = match self {
+            Event::Instructions => &mut counters.instructions,
+            Event::Cycles => &mut counters.cycles,
+            Event::BranchMisses => &mut counters.branch_misses,
+            Event::CacheMisses => &mut counters.cache_misses,
+            Event::TaskClock => &mut counters.task_clock_ns,
+            Event::PageFaults => &mut counters.page_faults,
+            Event::ContextSwitches => &mut counters.context_switches,
+        };
+        *field = Some(value);
+    }
+}
+
+/// A set of counters following the current thread, opened once and reused for every run
+pub struct PerfGroup {
+    counters: Vec<sys::Counter>,
+    availability: Availability,
+}
+
+impl PerfGroup {
+    /// Opens every hardware counter the kernel lets us have, falling back to software counters
+    /// when there are none
+    pub fn open() -> Self {
+        let open = |events: &[Event]| -> Vec<sys::Counter> {
+            events
+                .iter()
+                .filter_map(|&e| sys::Counter::open(e))
+                .collect()
+        };
+        let counters = open(&HARDWARE_EVENTS);
+        if !counters.is_empty() {
+            return PerfGroup {
+                counters,
+                availability: Availability::Hardware,
+            };
+        }
+        let counters = open(&SOFTWARE_EVENTS);
+        let availability = if counters.is_empty() {
+            Availability::Unavailable
+        } else {
+            Availability::Software
+        };
+        PerfGroup {
+            counters,
+            availability,
+        }
+    }
+
+    pub fn availability(&self) -> Availability {
+        self.availability
+    }
+
+    /// Counts the events that happen while running `f`
+    pub fn measure<R>(&self, f: impl FnOnce() -> R) -> (R, PerfCounters) {
+        for counter in &self.counters {
+            counter.reset_and_enable();
+        }
+        let result = f();
+        for counter in &self.counters {
+            counter.disable();
+        }
+        let mut values = PerfCounters::default();
+        for counter in &self.counters {
+            if let Some(value) = counter.read() {
+                counter.event.store(&mut values, value);
+            }
+        }
+        (result, values)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    use super::Event;
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_TYPE_SOFTWARE: u32 = 1;
+
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+    const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+    const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+    const PERF_COUNT_SW_TASK_CLOCK: u64 = 1;
+    const PERF_COUNT_SW_PAGE_FAULTS: u64 = 2;
+    const PERF_COUNT_SW_CONTEXT_SWITCHES: u64 = 3;
+
+    const ATTR_DISABLED: u64 = 1 << 0;
+    const ATTR_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const ATTR_EXCLUDE_HV: u64 = 1 << 6;
+
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+    const PERF_EVENT_IOC_RESET: libc::c_ulong = 0x2403;
+
+    /// The first published version of `struct perf_event_attr`, which every kernel accepts
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        kind: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+    }
+
+    pub struct Counter {
+        fd: OwnedFd,
+        pub event: Event,
+    }
+
+    impl Counter {
+        pub fn open(event: Event) -> Option<Self> {
+            let (kind, config) = match event {
+                Event::Instructions => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_INSTRUCTIONS),
+                Event::Cycles => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CPU_CYCLES),
+                Event::BranchMisses => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_BRANCH_MISSES),
+                Event::CacheMisses => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CACHE_MISSES),
+                Event::TaskClock => (PERF_TYPE_SOFTWARE, PERF_COUNT_SW_TASK_CLOCK),
+                Event::PageFaults => (PERF_TYPE_SOFTWARE, PERF_COUNT_SW_PAGE_FAULTS),
+                Event::ContextSwitches => (PERF_TYPE_SOFTWARE, PERF_COUNT_SW_CONTEXT_SWITCHES),
+            };
+            let attr = PerfEventAttr {
+                kind,
+                size: std::mem::size_of::<PerfEventAttr>() as u32,
+                config,
+                flags: ATTR_DISABLED | ATTR_EXCLUDE_KERNEL | ATTR_EXCLUDE_HV,
+                ..Default::default()
+            };
+            // SAFETY: attr is a valid perf_event_attr of the size it claims to be, pid 0 and cpu -1
+            // follow the calling thread on any CPU
+            let fd = unsafe {
+                libc::syscall(
+                    libc::SYS_perf_event_open,
+                    &attr as *const PerfEventAttr,
+                    0,
+                    -1,
+                    -1,
+                    PERF_FLAG_FD_CLOEXEC,
+                )
+            };
+            if fd < 0 {
+                return None;
+            }
+            // SAFETY: the syscall succeeded, so fd is an open file descriptor owned by nobody else
+            let fd = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+            Some(Counter { fd, event })
+        }
+
+        fn ioctl(&self, request: libc::c_ulong) {
+            // SAFETY: these requests take no argument and the fd is a perf event
+            unsafe { libc::ioctl(self.fd.as_raw_fd(), request as _, 0) };
+        }
+
+        pub fn reset_and_enable(&self) {
+            self.ioctl(PERF_EVENT_IOC_RESET);
+            self.ioctl(PERF_EVENT_IOC_ENABLE);
+        }
+
+        pub fn disable(&self) {
+            self.ioctl(PERF_EVENT_IOC_DISABLE);
+        }
+
+        pub fn read(&self) -> Option<u64> {
+            let mut value = 0u64;
+            // SAFETY: reading a perf event without a read_format yields a single u64
+            let read = unsafe {
+                libc::read(
+                    self.fd.as_raw_fd(),
+                    &mut value as *mut u64 as *mut libc::c_void,
+                    std::mem::size_of::<u64>(),
+                )
+            };
+            (read == std::mem::size_of::<u64>() as isize).then_some(value)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use super::Event;
+
+    pub struct Counter {
+        pub event: Event,
+    }
+
+    impl Counter {
+        pub fn open(_: Event) -> Option<Self> {
+            None
+        }
+        pub fn reset_and_enable(&self) {}
+        pub fn disable(&self) {}
+        pub fn read(&self) -> Option<u64> {
+            None
+        }
+    }
+}
@@ -0,0 +1,1124 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use plotters::coord::ranged1d::{
+    DefaultFormatting, KeyPointHint, Ranged, SegmentedCoord, ValueFormatter,
+};
+use plotters::coord::types::RangedCoordi32;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::{coord::Shift, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::harness::CacheMode;
+use crate::report::{BenchmarkRecord, BenchmarkReport, Metric};
+use crate::stats::BoxSummary;
+
+type DrawResult = Result<(), Box<dyn Error>>;
+
+const MARGIN: u32 = 15;
+const BAR_STROKE: u32 = 2;
+
+struct Colors;
+
+macro_rules! colors {
+    ($($col:expr),*) => {
+        [$({let [r, g, b] = color_hex::color_from_hex!($col); (r,g,b) }),*]
+    };
+}
+
+impl Palette for Colors {
+    /// 0..16 are the base16 colors
+    /// 16..18 are the background, forground and cursor color
+    #[rustfmt::skip]
+    const COLORS: &'static [(u8, u8, u8)] = &colors!(
+        "#1E1E2F", "#F38BA8", "#A6E3A1", "#F9E2AF", "#89B4FA", "#F5C2E7", "#94E2D5", "#BAC2DE",
+        "#4e4e75", "#F38BA8", "#A6E3A1", "#F9E2AF", "#89B4FA", "#F5C2E7", "#94E2D5", "#A6ADC8",
+        "#1E1E2F", "#CDD6F4", "#8CAAEE",
+        "#5C5F77", "#D20F39", "#40A02B", "#DF8E1D", "#1E66F5", "#EA76CB", "#179299", "#ACB0BE",
+        "#6C6F85", "#D20F39", "#40A02B", "#DF8E1D", "#1E66F5", "#EA76CB", "#179299", "#BCC0CC",
+        "#EFF1F5", "#4C4F69", "#1E66F5"
+    );
+}
+
+/// Okabe and Ito's palette, which stays distinguishable with every common color vision deficiency
+#[rustfmt::skip]
+const COLORBLIND_SAFE: &[(u8, u8, u8)] = &colors!(
+    "#E69F00", "#56B4E9", "#009E73", "#F0E442", "#0072B2", "#D55E00", "#CC79A7", "#000000"
+);
+#[rustfmt::skip]
+const HIGH_CONTRAST: &[(u8, u8, u8)] = &colors!(
+    "#FFFF00", "#00FFFF", "#FF00FF", "#00FF00", "#FF8000", "#FFFFFF"
+);
+/// Far enough apart to tell bars apart when printed, which get outlined in black
+#[rustfmt::skip]
+const GRAYSCALE: &[(u8, u8, u8)] = &colors!(
+    "#1A1A1A", "#E6E6E6", "#666666", "#B3B3B3", "#404040", "#8C8C8C"
+);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Bright colors on black
+    HighContrast,
+    /// Colors that stay distinguishable with color vision deficiencies
+    ColorblindSafe,
+    /// Shades of gray on white, for printing
+    Grayscale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Chart {
+    /// One horizontal bar chart per input length and stability
+    Bar,
+    /// Like `bar`, with a box plot of the samples of each case in place of its bar
+    Box,
+    /// A grid of sorts against distributions colored by the ratio to the best sort, one chart
+    /// per input length
+    Heatmap,
+    /// The metric against input length on log-log axes, one panel per distribution
+    Scaling,
+    /// How many times faster than --reference every sort is on the same input, on a log axis,
+    /// one chart per input length
+    Speedup,
+}
+
+/// The colors of a theme, with every sort keeping the same color in all charts
+struct ThemeColors {
+    background: RGBColor,
+    foreground: RGBColor,
+    bold_line: RGBColor,
+    light_line: RGBColor,
+    /// Drawn around bars and legends
+    outline: RGBColor,
+    series: Vec<RGBColor>,
+    /// The heatmap colors of the best and the worst cases
+    heat: (RGBColor, RGBColor),
+    /// Sorts get the series color at their index in here
+    sorts: Vec<String>,
+}
+
+impl ThemeColors {
+    fn new(theme: Theme, sorts: &[&str]) -> Self {
+        let rgb = |&(r, g, b): &(u8, u8, u8)| RGBColor(r, g, b);
+        let black = RGBColor(0, 0, 0);
+        let white = RGBColor(255, 255, 255);
+        let count = sorts.len();
+        let sorts = sorts.iter().map(|s| s.to_string()).collect();
+        let mut colors = match theme {
+            // The base16 palettes, dark is at 0..19 and light at 19..38
+            Theme::Dark | Theme::Light => {
+                let base = if theme == Theme::Dark { 0 } else { 19 };
+                let pick = |idx: usize| rgb(&Colors::COLORS[base + idx]);
+                ThemeColors {
+                    background: pick(16),
+                    foreground: pick(17),
+                    bold_line: pick(7),
+                    // The light palette's 8 is darker than its 7, so it would outweigh the bold lines
+                    light_line: pick(if theme == Theme::Dark { 8 } else { 15 }),
+                    outline: pick(8),
+                    series: (1..7).map(pick).collect(),
+                    heat: (pick(2), pick(1)),
+                    sorts,
+                }
+            }
+            Theme::HighContrast => ThemeColors {
+                background: black,
+                foreground: white,
+                bold_line: RGBColor(160, 160, 160),
+                light_line: RGBColor(64, 64, 64),
+                outline: white,
+                series: HIGH_CONTRAST.iter().map(rgb).collect(),
+                heat: (rgb(&HIGH_CONTRAST[3]), rgb(&HIGH_CONTRAST[2])),
+                sorts,
+            },
+            Theme::ColorblindSafe | Theme::Grayscale => ThemeColors {
+                background: white,
+                foreground: black,
+                bold_line: RGBColor(160, 160, 160),
+                light_line: RGBColor(224, 224, 224),
+                outline: black,
+                series: if theme == Theme::Grayscale {
+                    GRAYSCALE.iter().map(rgb).collect()
+                } else {
+                    COLORBLIND_SAFE.iter().map(rgb).collect()
+                },
+                heat: if theme == Theme::Grayscale {
+                    (rgb(&GRAYSCALE[1]), rgb(&GRAYSCALE[0]))
+                } else {
+                    (rgb(&COLORBLIND_SAFE[4]), rgb(&COLORBLIND_SAFE[5]))
+                },
+                sorts,
+            },
+        };
+        colors.extend_series(theme, count);
+        colors
+    }
+
+    /// Palettes only have a handful of colors, so sorts past them get extra ones spaced by the
+    /// golden ratio rather than reusing a color another sort already has
+    fn extend_series(&mut self, theme: Theme, count: usize) {
+        const GOLDEN_RATIO: f64 = 0.618_033_988_75;
+        // Light enough to stand out against dark backgrounds, dark enough against light ones
+        let lightness = match theme {
+            Theme::Dark | Theme::HighContrast => 0.7,
+            Theme::Light | Theme::ColorblindSafe | Theme::Grayscale => 0.4,
+        };
+        let extra = count.saturating_sub(self.series.len());
+        self.series.extend((1..=extra).map(|i| {
+            let t = (i as f64 * GOLDEN_RATIO).fract();
+            let color = if theme == Theme::Grayscale {
+                HSLColor(0., 0., 0.15 + 0.7 * t)
+            } else {
+                HSLColor(t, 0.7, lightness)
+            };
+            let RGBAColor(r, g, b, _) = color.to_rgba();
+            RGBColor(r, g, b)
+        }));
+    }
+
+    /// The heatmap color `t` of the way from the best to the worst case
+    fn heat_color(&self, t: f64) -> RGBColor {
+        let (RGBColor(r0, g0, b0), RGBColor(r1, g1, b1)) = self.heat;
+        let mix = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t).round() as u8;
+        RGBColor(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+    }
+
+    fn sort_color(&self, sort: &str) -> RGBColor {
+        let idx = self.sorts.iter().position(|s| s == sort).unwrap_or(0);
+        self.series[idx]
+    }
+}
+
+/// The file formats charts can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Where charts are written and what they look like on disk
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub dir: PathBuf,
+    pub format: ImageFormat,
+    /// In pixels
+    pub resolution: (u32, u32),
+}
+
+/// What is drawn, independent of where it goes
+#[derive(Debug, Clone)]
+pub struct ChartOptions<'a> {
+    pub charts: &'a [Chart],
+    pub theme: Theme,
+    pub metric: Metric,
+    /// The sort the speedup chart compares every other one against
+    pub reference: Option<&'a str>,
+    /// Whether bar and box charts plot the metric on a logarithmic axis
+    pub log_scale: bool,
+}
+
+/// Draws every chart of the kinds in `options`, returning the files written
+pub fn draw(
+    report: &BenchmarkReport,
+    options: &ChartOptions,
+    target: &Target,
+) -> Result<Vec<PathBuf>, String> {
+    let mut canvas = Canvas::Files {
+        target: target.clone(),
+        written: Vec::new(),
+    };
+    draw_all(report, options, &mut canvas)?;
+    match canvas {
+        Canvas::Files { written, .. } => Ok(written),
+        Canvas::Memory { .. } => unreachable!("drawn to files"),
+    }
+}
+
+/// Draws every chart of the kinds in `options` as SVG, returning the name and markup of each
+pub fn render_svg(
+    report: &BenchmarkReport,
+    options: &ChartOptions,
+    resolution: (u32, u32),
+) -> Result<Vec<(String, String)>, String> {
+    let mut canvas = Canvas::Memory {
+        resolution,
+        svgs: Vec::new(),
+    };
+    draw_all(report, options, &mut canvas)?;
+    match canvas {
+        Canvas::Memory { svgs, .. } => Ok(svgs),
+        Canvas::Files { .. } => unreachable!("drawn to memory"),
+    }
+}
+
+/// Where the charts go, and what has been drawn so far
+enum Canvas {
+    Files {
+        target: Target,
+        written: Vec<PathBuf>,
+    },
+    Memory {
+        resolution: (u32, u32),
+        svgs: Vec<(String, String)>,
+    },
+}
+
+fn draw_all(
+    report: &BenchmarkReport,
+    options: &ChartOptions,
+    canvas: &mut Canvas,
+) -> Result<(), String> {
+    let &ChartOptions {
+        charts,
+        theme,
+        metric,
+        reference,
+        log_scale,
+    } = options;
+    let colors = ThemeColors::new(theme, &report.sorts());
+    for boxes in [false, true] {
+        let chart = if boxes { Chart::Box } else { Chart::Bar };
+        if charts.contains(&chart) {
+            draw_charts(report, &colors, metric, boxes, log_scale, canvas)?;
+        }
+    }
+    if charts.contains(&Chart::Heatmap) {
+        draw_heatmaps(report, &colors, metric, canvas)?;
+    }
+    if charts.contains(&Chart::Scaling) {
+        draw_scaling(report, &colors, metric, canvas)?;
+    }
+    if charts.contains(&Chart::Speedup) {
+        let reference =
+            reference.ok_or("the speedup chart needs a reference sort, see --reference")?;
+        draw_speedup(report, &colors, metric, reference, canvas)?;
+    }
+    Ok(())
+}
+
+/// Calls `draw` with a drawing area for the chart called `name` on `canvas`
+macro_rules! draw_to {
+    ($canvas:expr, $name:expr, $draw:expr) => {{
+        let name: String = $name;
+        match $canvas {
+            Canvas::Files { target, written } => {
+                let path = target
+                    .dir
+                    .join(format!("{name}.{}", target.format.extension()));
+                match target.format {
+                    ImageFormat::Svg => {
+                        $draw(SVGBackend::new(&path, target.resolution).into_drawing_area())
+                    }
+                    ImageFormat::Png => {
+                        $draw(BitMapBackend::new(&path, target.resolution).into_drawing_area())
+                    }
+                }
+                .map_err(|e| format!("failed to draw {}: {e}", path.display()))?;
+                written.push(path);
+            }
+            Canvas::Memory { resolution, svgs } => {
+                let mut svg = String::new();
+                $draw(SVGBackend::with_string(&mut svg, *resolution).into_drawing_area())
+                    .map_err(|e| format!("failed to draw {name}: {e}"))?;
+                svgs.push((name, svg));
+            }
+        }
+    }};
+}
+
+/// Sizes are given for 1080 pixel high charts, this scales them to the height of `root`
+fn scale<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>) -> f64 {
+    root.dim_in_pixel().1 as f64 / 1080.
+}
+
+/// Tells apart the files of the cache modes, warm cache charts keep their old names
+fn cache_suffix(cache: CacheMode) -> (&'static str, &'static str) {
+    match cache {
+        CacheMode::Warm => ("", ""),
+        CacheMode::Cold => ("_cold", " with a cold cache"),
+    }
+}
+
+/// Draws a bar per case, or a box plot of its samples if `boxes` is set, one chart per length,
+/// stability and cache mode
+fn draw_charts(
+    report: &BenchmarkReport,
+    colors: &ThemeColors,
+    metric: Metric,
+    boxes: bool,
+    log_scale: bool,
+    canvas: &mut Canvas,
+) -> Result<(), String> {
+    let cache_modes = report.cache_modes();
+    for (stable, name) in [(false, "unstable"), (true, "stable")] {
+        for &cache in &cache_modes {
+            for size in report.lengths() {
+                let data =
+                    report.by_sort(|r| r.stable == stable && r.cache == cache && r.length == size);
+                if data.is_empty() {
+                    continue;
+                }
+                let (suffix, regime) = cache_suffix(cache);
+                let prefix = if boxes { "box_" } else { "" };
+                let caption = format!("Sorting {size} elements{regime}");
+                draw_to!(canvas, format!("{prefix}{name}_{size}{suffix}"), |root| {
+                    bar_chart(root, &data, &caption, colors, metric, boxes, log_scale)
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn bar_chart<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    data: &[(&str, Vec<&BenchmarkRecord>)],
+    caption: &str,
+    colors: &ThemeColors,
+    metric: Metric,
+    boxes: bool,
+    log_scale: bool,
+) -> DrawResult
+where
+    DB::ErrorType: 'static,
+{
+    let scale = scale(&root);
+    let font = |size| {
+        FontFamily::SansSerif
+            .into_font()
+            .resize(size * scale)
+            .with_color(colors.foreground)
+    };
+    let label_area = (40. * scale) as u32;
+
+    root.fill(&colors.background)?;
+
+    let distr_count = data.iter().map(|(_, d)| d.len()).max().unwrap_or(0) as i32;
+    let method_count = data.len() as i32;
+    let values = || {
+        data.iter()
+            .flat_map(|(_, v)| v.iter())
+            .flat_map(|x| {
+                let samples = if boxes { x.samples(metric) } else { vec![] };
+                samples.into_iter().chain([x.metric(metric)])
+            })
+            .filter(|v| v.is_finite())
+    };
+    // An empty range can't be drawn, which all zero metrics would give
+    let max_value = match values().fold(0., f64::max) {
+        max if max > 0. => max,
+        _ => 1.,
+    };
+
+    let (left, right) = root.split_horizontally((20).percent());
+    let mut builder = ChartBuilder::on(&right);
+    builder
+        .caption(caption, font(40.))
+        .margin_top(MARGIN)
+        .margin_right(MARGIN)
+        .margin_bottom(MARGIN)
+        .set_label_area_size(LabelAreaPosition::Bottom, label_area);
+    let rows = (0..(method_count + 1) * (distr_count)).into_segmented();
+    label_distributions(&left, data, colors, label_area)?;
+    if log_scale {
+        // Log axes can't start at zero, so bars start a bit short of the smallest value
+        let min_value = values().filter(|&v| v > 0.).fold(max_value, f64::min);
+        let origin = min_value / 2.;
+        let chart = builder.build_cartesian_2d((origin..max_value * 2.).log_scale(), rows)?;
+        draw_bars(chart, data, colors, metric, boxes, origin, scale)
+    } else {
+        let chart = builder.build_cartesian_2d(0f64..max_value * 1.15, rows)?;
+        draw_bars(chart, data, colors, metric, boxes, 0., scale)
+    }?;
+    root.present()?;
+    Ok(())
+}
+
+/// Draws the bars or box plots of [bar_chart] on either a linear or a log axis, bars start at
+/// `origin`
+fn draw_bars<'a, DB, X>(
+    mut chart: ChartContext<'a, DB, Cartesian2d<X, SegmentedCoord<RangedCoordi32>>>,
+    data: &[(&str, Vec<&BenchmarkRecord>)],
+    colors: &ThemeColors,
+    metric: Metric,
+    boxes: bool,
+    origin: f64,
+    scale: f64,
+) -> DrawResult
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    X: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let font = |size| {
+        FontFamily::SansSerif
+            .into_font()
+            .resize(size * scale)
+            .with_color(colors.foreground)
+    };
+    let method_count = data.len() as i32;
+    let mut mesh = chart.configure_mesh();
+    mesh.disable_y_mesh()
+        .disable_y_axis()
+        .axis_style(colors.foreground)
+        .bold_line_style(colors.bold_line)
+        .light_line_style(colors.light_line)
+        .label_style(font(22.))
+        .x_desc(axis_desc(metric))
+        .axis_desc_style(font(22.));
+    let time_label = |&ns: &f64| duration_label(ns);
+    if is_time(metric) {
+        mesh.x_label_formatter(&time_label);
+    }
+    mesh.draw()?;
+    for (ytop, (sort, results)) in data.iter().enumerate() {
+        let color = colors.sort_color(sort);
+        let rows = bar_rows(ytop, method_count, results);
+        let outline = colors.outline.stroke_width(BAR_STROKE);
+        chart
+            .draw_series(rows.iter().flat_map(|&(y, record)| {
+                if boxes {
+                    return box_plot(&record.samples(metric), y, color.filled(), outline);
+                }
+                let coords = [
+                    (origin, SegmentValue::Exact(y)),
+                    (
+                        record.metric(metric).max(origin),
+                        SegmentValue::Exact(y + 1),
+                    ),
+                ];
+                vec![
+                    Rectangle::new(coords.clone(), color.filled()).into_dyn(),
+                    Rectangle::new(coords, outline).into_dyn(),
+                ]
+            }))?
+            .label(*sort)
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(5))
+            });
+    }
+
+    chart
+        .configure_series_labels()
+        .border_style(colors.outline)
+        .background_style(colors.background.mix(0.8))
+        .label_font(font(30.))
+        .draw()?;
+    Ok(())
+}
+
+/// Whether the metric is in nanoseconds, which axes label in whichever unit suits the value
+fn is_time(metric: Metric) -> bool {
+    matches!(metric, Metric::WallTime | Metric::TimePerElement)
+}
+
+/// The description of an axis showing `metric`, without the unit if the labels carry it
+fn axis_desc(metric: Metric) -> &'static str {
+    match metric {
+        Metric::WallTime => "Time",
+        Metric::TimePerElement => "Time per element",
+        _ => metric.label(),
+    }
+}
+
+/// Up to three significant digits of `ns`, in the largest unit that keeps them above one
+fn duration_label(ns: f64) -> String {
+    let (value, unit) = match ns.abs() {
+        n if n >= 1e9 => (ns / 1e9, "s"),
+        n if n >= 1e6 => (ns / 1e6, "ms"),
+        n if n >= 1e3 => (ns / 1e3, "µs"),
+        _ => (ns, "ns"),
+    };
+    let decimals = match value.abs() {
+        v if v >= 100. => 0,
+        v if v >= 10. => 1,
+        _ => 2,
+    };
+    let value = format!("{value:.decimals$}");
+    let value = if value.contains('.') {
+        value.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &value
+    };
+    format!("{value} {unit}")
+}
+
+/// Draws the metric of `reference` divided by that of every sort on the same input, which is how
+/// many times faster than it they are, one chart per length and cache mode
+fn draw_speedup(
+    report: &BenchmarkReport,
+    colors: &ThemeColors,
+    metric: Metric,
+    reference: &str,
+    canvas: &mut Canvas,
+) -> Result<(), String> {
+    let reference = report
+        .sorts()
+        .into_iter()
+        .find(|sort| sort.eq_ignore_ascii_case(reference))
+        .ok_or_else(|| format!("reference sort `{reference}` is not in the results"))?;
+    for cache in report.cache_modes() {
+        for size in report.lengths() {
+            let data = report.by_sort(|r| r.cache == cache && r.length == size);
+            if data.is_empty() {
+                continue;
+            }
+            let (suffix, regime) = cache_suffix(cache);
+            let caption = format!("Sorting {size} elements relative to {reference}{regime}");
+            draw_to!(canvas, format!("speedup_{size}{suffix}"), |root| {
+                speedup_chart(root, &data, reference, &caption, colors, metric)
+            });
+        }
+    }
+    Ok(())
+}
+
+fn speedup_chart<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    data: &[(&str, Vec<&BenchmarkRecord>)],
+    reference: &str,
+    caption: &str,
+    colors: &ThemeColors,
+    metric: Metric,
+) -> DrawResult
+where
+    DB::ErrorType: 'static,
+{
+    let scale = scale(&root);
+    let font = |size| {
+        FontFamily::SansSerif
+            .into_font()
+            .resize(size * scale)
+            .with_color(colors.foreground)
+    };
+    let label_area = (40. * scale) as u32;
+
+    root.fill(&colors.background)?;
+
+    let distr_count = data.iter().map(|(_, d)| d.len()).max().unwrap_or(0) as i32;
+    let method_count = data.len() as i32;
+    let baseline = |distribution: &str| {
+        data.iter()
+            .filter(|(sort, _)| *sort == reference)
+            .flat_map(|(_, records)| records.iter())
+            .find(|r| r.distribution == distribution && r.result.success)
+            .map(|r| r.metric(metric))
+    };
+    // Failed cases and zeroes, which memory metrics often are, have no meaningful speedup
+    let ratio = |record: &BenchmarkRecord| {
+        let value = record.metric(metric);
+        let baseline = baseline(&record.distribution)?;
+        (record.result.success && value > 0. && baseline > 0.).then(|| baseline / value)
+    };
+    let (min_ratio, max_ratio) = data
+        .iter()
+        .flat_map(|(_, records)| records.iter().filter_map(|r| ratio(r)))
+        .fold((1f64, 1f64), |(min, max), r| (min.min(r), max.max(r)));
+
+    let (left, right) = root.split_horizontally((20).percent());
+    let mut chart = ChartBuilder::on(&right)
+        .caption(caption, font(40.))
+        .margin_top(MARGIN)
+        .margin_right(MARGIN)
+        .margin_bottom(MARGIN)
+        .set_label_area_size(LabelAreaPosition::Bottom, label_area)
+        .build_cartesian_2d(
+            (min_ratio / 1.5..max_ratio * 1.5).log_scale(),
+            (0..(method_count + 1) * (distr_count)).into_segmented(),
+        )?;
+    chart
+        .configure_mesh()
+        .disable_y_mesh()
+        .disable_y_axis()
+        .axis_style(colors.foreground)
+        .bold_line_style(colors.bold_line)
+        .light_line_style(colors.light_line)
+        .label_style(font(22.))
+        .x_label_formatter(&|&ratio| ratio_label(ratio))
+        .x_desc(format!("Speedup over {reference}, higher is better"))
+        .axis_desc_style(font(22.))
+        .draw()?;
+    label_distributions(&left, data, colors, label_area)?;
+    let outline = colors.outline.stroke_width(BAR_STROKE);
+    for (ytop, (sort, results)) in data.iter().enumerate() {
+        let color = colors.sort_color(sort);
+        let rows = bar_rows(ytop, method_count, results);
+        chart
+            .draw_series(rows.iter().flat_map(|&(y, record)| {
+                let Some(ratio) = ratio(record) else {
+                    return vec![];
+                };
+                // Bars grow from the reference, right for faster and left for slower sorts
+                let coords = [
+                    (1., SegmentValue::Exact(y)),
+                    (ratio, SegmentValue::Exact(y + 1)),
+                ];
+                vec![
+                    Rectangle::new(coords.clone(), color.filled()),
+                    Rectangle::new(coords, outline),
+                ]
+            }))?
+            .label(*sort)
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(5))
+            });
+    }
+    chart.draw_series([PathElement::new(
+        vec![
+            (1., SegmentValue::Exact(0)),
+            (1., SegmentValue::Exact((method_count + 1) * distr_count)),
+        ],
+        colors.foreground.stroke_width(BAR_STROKE),
+    )])?;
+
+    chart
+        .configure_series_labels()
+        .border_style(colors.outline)
+        .background_style(colors.background.mix(0.8))
+        .label_font(font(30.))
+        .draw()?;
+    root.present()?;
+    Ok(())
+}
+
+/// As many decimals as it takes to show the first significant digit
+fn ratio_label(ratio: f64) -> String {
+    let decimals = (-ratio.log10().floor()).max(0.) as usize;
+    format!("{ratio:.decimals$}×")
+}
+
+/// The segment of each of the records of the `ytop`th sort, the sorts' bars are interleaved so
+/// that every distribution gets a group of bars with a gap above it
+fn bar_rows<'a>(
+    ytop: usize,
+    method_count: i32,
+    results: &[&'a BenchmarkRecord],
+) -> Vec<(i32, &'a BenchmarkRecord)> {
+    (ytop as i32 + 1..)
+        .step_by(method_count as usize + 1)
+        .zip(results.iter().copied())
+        .collect()
+}
+
+/// Writes the distribution of every group of bars in `left`, which sits beside the chart
+fn label_distributions<DB: DrawingBackend>(
+    left: &DrawingArea<DB, Shift>,
+    data: &[(&str, Vec<&BenchmarkRecord>)],
+    colors: &ThemeColors,
+    label_area: u32,
+) -> DrawResult
+where
+    DB::ErrorType: 'static,
+{
+    let scale = scale(left);
+    let distr_count = data.iter().map(|(_, d)| d.len()).max().unwrap_or(0) as i32;
+    let method_count = data.len() as i32;
+    let text = ChartBuilder::on(left)
+        .margin_left(MARGIN)
+        .margin_top(MARGIN)
+        .margin_bottom(MARGIN)
+        .set_label_area_size(LabelAreaPosition::Bottom, label_area)
+        .build_cartesian_2d(
+            0..1,
+            (0..(method_count + 1) * (distr_count)).into_segmented(),
+        )?;
+    let font_color = colors.foreground;
+    let text_style = || {
+        let mut style = font_color.into_text_style(text.plotting_area());
+        style.font = FontDesc::new(FontFamily::SansSerif, 26. * scale, FontStyle::Normal);
+        style
+    };
+    for (ytop, (_, results)) in data.iter().enumerate() {
+        for (y, record) in bar_rows(ytop, method_count, results) {
+            text.plotting_area().draw(&Text::new(
+                record.distribution.clone(),
+                (
+                    0,
+                    SegmentValue::Exact(
+                        (y - 1) / (method_count + 1) * (method_count + 1)
+                            + method_count / 2
+                            + method_count % 2
+                            + 1,
+                    ),
+                ),
+                text_style(),
+            ))?;
+        }
+    }
+    text.plotting_area().draw(&Text::new(
+        "Input distribution",
+        (0, SegmentValue::Exact((method_count + 1) * (distr_count))),
+        text_style(),
+    ))?;
+    Ok(())
+}
+
+/// The elements of a horizontal box plot of `samples` filling segment `y`
+fn box_plot<DB: DrawingBackend>(
+    samples: &[f64],
+    y: i32,
+    fill: ShapeStyle,
+    outline: ShapeStyle,
+) -> Vec<DynElement<'static, DB, (f64, SegmentValue<i32>)>> {
+    let Some(summary) = BoxSummary::new(samples) else {
+        return Vec::new();
+    };
+    let (top, center, bottom) = (
+        SegmentValue::Exact(y),
+        SegmentValue::CenterOf(y),
+        SegmentValue::Exact(y + 1),
+    );
+    let across = |x: f64| PathElement::new(vec![(x, top.clone()), (x, bottom.clone())], outline);
+    let mut elements = vec![
+        PathElement::new(
+            vec![(summary.low, center.clone()), (summary.q1, center.clone())],
+            outline,
+        )
+        .into_dyn(),
+        PathElement::new(
+            vec![(summary.q3, center.clone()), (summary.high, center.clone())],
+            outline,
+        )
+        .into_dyn(),
+        across(summary.low).into_dyn(),
+        across(summary.high).into_dyn(),
+        Rectangle::new(
+            [(summary.q1, top.clone()), (summary.q3, bottom.clone())],
+            fill,
+        )
+        .into_dyn(),
+        Rectangle::new(
+            [(summary.q1, top.clone()), (summary.q3, bottom.clone())],
+            outline,
+        )
+        .into_dyn(),
+        across(summary.median).into_dyn(),
+    ];
+    elements.extend(
+        summary
+            .outliers
+            .iter()
+            .map(|&x| Circle::new((x, center.clone()), 4, fill).into_dyn()),
+    );
+    elements
+}
+
+/// Draws a grid of every sort on every distribution colored by how far behind the best sort of
+/// the distribution it is, one chart per length and cache mode
+fn draw_heatmaps(
+    report: &BenchmarkReport,
+    colors: &ThemeColors,
+    metric: Metric,
+    canvas: &mut Canvas,
+) -> Result<(), String> {
+    let distributions = report.distributions();
+    for cache in report.cache_modes() {
+        for size in report.lengths() {
+            let data = report.by_sort(|r| r.cache == cache && r.length == size);
+            if data.is_empty() {
+                continue;
+            }
+            let (suffix, regime) = cache_suffix(cache);
+            let caption = format!("Sorting {size} elements{regime}");
+            draw_to!(canvas, format!("heatmap_{size}{suffix}"), |root| {
+                heatmap(root, &data, &distributions, &caption, colors, metric)
+            });
+        }
+    }
+    Ok(())
+}
+
+/// An axis of `count` unit wide cells, labelled at their centers
+struct Cells(usize);
+
+impl Ranged for Cells {
+    type FormatOption = DefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        let fraction = value / self.0 as f64;
+        limit.0 + (fraction * (limit.1 - limit.0) as f64).round() as i32
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, _hint: Hint) -> Vec<f64> {
+        (0..self.0).map(|idx| idx as f64 + 0.5).collect()
+    }
+
+    fn range(&self) -> std::ops::Range<f64> {
+        0.0..self.0 as f64
+    }
+}
+
+fn heatmap<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    data: &[(&str, Vec<&BenchmarkRecord>)],
+    distributions: &[&str],
+    caption: &str,
+    colors: &ThemeColors,
+    metric: Metric,
+) -> DrawResult
+where
+    DB::ErrorType: 'static,
+{
+    let scale = scale(&root);
+    let font = |size| {
+        FontFamily::SansSerif
+            .into_font()
+            .resize(size * scale)
+            .with_color(colors.foreground)
+    };
+
+    root.fill(&colors.background)?;
+
+    // The ratio of every case to the best one of its distribution, infinite if the best is zero
+    let mut cells = Vec::new();
+    for (row, distribution) in distributions.iter().enumerate() {
+        let records: Vec<(usize, &BenchmarkRecord)> = data
+            .iter()
+            .enumerate()
+            .filter_map(|(column, (_, records))| {
+                let record = records.iter().find(|r| r.distribution == *distribution)?;
+                Some((column, *record))
+            })
+            .collect();
+        let best = records
+            .iter()
+            .filter(|(_, r)| r.result.success)
+            .map(|(_, r)| r.metric(metric))
+            .fold(f64::INFINITY, f64::min);
+        for (column, record) in records {
+            let value = record.metric(metric);
+            let ratio = if value <= best { 1. } else { value / best };
+            cells.push((column, row, record, ratio));
+        }
+    }
+    let worst = cells
+        .iter()
+        .filter(|(_, _, record, ratio)| record.result.success && ratio.is_finite())
+        .map(|&(_, _, _, ratio)| ratio)
+        .fold(1f64, f64::max);
+
+    let columns = data.len();
+    let rows = distributions.len();
+    let mut chart = ChartBuilder::on(&root)
+        .caption(caption, font(40.))
+        .margin(MARGIN)
+        .margin_right(3 * MARGIN)
+        .set_label_area_size(LabelAreaPosition::Left, (260. * scale) as u32)
+        .set_label_area_size(LabelAreaPosition::Bottom, (70. * scale) as u32)
+        .build_cartesian_2d(Cells(columns), Cells(rows))?;
+    // Rows are numbered bottom up, distributions are listed top down
+    let row_of = |idx: usize| (rows - 1 - idx) as f64;
+    let label = |names: Vec<&str>, center: &f64| {
+        names
+            .get(center.floor() as usize)
+            .copied()
+            .unwrap_or("")
+            .to_string()
+    };
+    let sort_names: Vec<&str> = data.iter().map(|(sort, _)| *sort).collect();
+    let distribution_names: Vec<&str> = distributions.iter().rev().copied().collect();
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .axis_style(colors.foreground)
+        .label_style(font(22.))
+        .x_labels(columns)
+        .y_labels(rows)
+        .x_label_formatter(&|center| label(sort_names.clone(), center))
+        .y_label_formatter(&|center| label(distribution_names.clone(), center))
+        .x_desc(format!(
+            "{}, colored from the best sort of each distribution to {} as much",
+            metric.label(),
+            ratio_label(worst)
+        ))
+        .axis_desc_style(font(22.))
+        .draw()?;
+
+    let outline = colors.background.stroke_width(BAR_STROKE);
+    for &(column, row, record, ratio) in &cells {
+        let (x, y) = (column as f64, row_of(row));
+        let corners = [(x, y), (x + 1., y + 1.)];
+        let center = (x + 0.5, y + 0.5);
+        let (fill, text) = if record.result.success {
+            let t = if worst > 1. {
+                (ratio.ln() / worst.ln()).min(1.)
+            } else {
+                0.
+            };
+            let value = record.metric(metric);
+            let text = if ratio.is_finite() && ratio > 1. {
+                let decimals = if ratio < 10. { 1 } else { 0 };
+                format!("{value:.2} ({ratio:.decimals$}×)")
+            } else {
+                format!("{value:.2}")
+            };
+            (colors.heat_color(t), text)
+        } else {
+            (colors.background, "not sorted".to_string())
+        };
+        // Dark text on light cells and the other way around
+        let RGBColor(r, g, b) = fill;
+        let luminance = 0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64;
+        let text_color = if luminance > 128. {
+            RGBColor(0, 0, 0)
+        } else {
+            RGBColor(255, 255, 255)
+        };
+        chart.draw_series([
+            Rectangle::new(corners, fill.filled()),
+            Rectangle::new(corners, outline),
+        ])?;
+        chart.draw_series([Text::new(
+            text,
+            center,
+            FontFamily::SansSerif
+                .into_font()
+                .resize(22. * scale)
+                .with_color(text_color)
+                .into_text_style(&root)
+                .pos(Pos::new(HPos::Center, VPos::Center)),
+        )])?;
+    }
+    root.present()?;
+    Ok(())
+}
+
+/// Draws the metric against input length for every sort, one panel per distribution
+fn draw_scaling(
+    report: &BenchmarkReport,
+    colors: &ThemeColors,
+    metric: Metric,
+    canvas: &mut Canvas,
+) -> Result<(), String> {
+    // The length axis is logarithmic, which has no place for empty inputs
+    let lengths: Vec<usize> = report.lengths().into_iter().filter(|&l| l > 0).collect();
+    let (Some(&shortest), Some(&longest)) = (lengths.first(), lengths.last()) else {
+        return Ok(());
+    };
+    if shortest == longest {
+        return Ok(());
+    }
+    for cache in report.cache_modes() {
+        let (suffix, regime) = cache_suffix(cache);
+        let caption = format!("Scaling with input length{regime}");
+        let sorts = report.by_sort(|r| r.cache == cache);
+        draw_to!(canvas, format!("scaling{suffix}"), |root| scaling_chart(
+            root,
+            &sorts,
+            &report.distributions(),
+            (shortest, longest),
+            &caption,
+            colors,
+            metric
+        ));
+    }
+    Ok(())
+}
+
+fn scaling_chart<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    sorts: &[(&str, Vec<&BenchmarkRecord>)],
+    distributions: &[&str],
+    (shortest, longest): (usize, usize),
+    caption: &str,
+    colors: &ThemeColors,
+    metric: Metric,
+) -> DrawResult
+where
+    DB::ErrorType: 'static,
+{
+    let scale = scale(&root);
+    let font = |size| {
+        FontFamily::SansSerif
+            .into_font()
+            .resize(size * scale)
+            .with_color(colors.foreground)
+    };
+    let columns = (distributions.len() as f64).sqrt().ceil() as usize;
+    let rows = distributions.len().div_ceil(columns);
+
+    root.fill(&colors.background)?;
+    let root = root.titled(caption, font(40.).into_text_style(&root))?;
+    for (panel, distribution) in root.split_evenly((rows, columns)).iter().zip(distributions) {
+        // Log axes can't show zeroes, which memory metrics often are
+        let series: Vec<(&str, Vec<(f64, f64)>)> = sorts
+            .iter()
+            .map(|(sort, records)| {
+                let points = records
+                    .iter()
+                    .filter(|r| r.distribution == *distribution && r.length > 0)
+                    .map(|r| (r.length as f64, r.metric(metric)))
+                    .filter(|&(_, value)| value > 0.)
+                    .collect();
+                (*sort, points)
+            })
+            .collect();
+        let (min_value, max_value) = series
+            .iter()
+            .flat_map(|(_, points)| points.iter().map(|&(_, value)| value))
+            .fold((f64::INFINITY, 0f64), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+        if max_value == 0. {
+            continue;
+        }
+        let mut chart = ChartBuilder::on(panel)
+            .caption(*distribution, font(26.))
+            .margin(MARGIN)
+            .margin_right(3 * MARGIN)
+            .set_label_area_size(LabelAreaPosition::Left, (70. * scale) as u32)
+            .set_label_area_size(LabelAreaPosition::Bottom, (40. * scale) as u32)
+            .build_cartesian_2d(
+                (shortest as f64..longest as f64).log_scale(),
+                (min_value / 1.5..max_value * 1.5).log_scale(),
+            )?;
+        let mut mesh = chart.configure_mesh();
+        mesh.axis_style(colors.foreground)
+            .bold_line_style(colors.bold_line)
+            .light_line_style(colors.light_line)
+            .label_style(font(16.))
+            .x_label_formatter(&|length| format!("{length:.0}"))
+            .x_desc("Input length")
+            .y_desc(axis_desc(metric))
+            .axis_desc_style(font(18.));
+        let time_label = |&ns: &f64| duration_label(ns);
+        if is_time(metric) {
+            mesh.y_label_formatter(&time_label);
+        }
+        mesh.draw()?;
+        for (sort, points) in &series {
+            let color = colors.sort_color(sort);
+            chart
+                .draw_series(LineSeries::new(points.clone(), color.stroke_width(3)))?
+                .label(*sort)
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(5))
+                });
+            chart.draw_series(points.iter().map(|&p| Circle::new(p, 4, color.filled())))?;
+        }
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .border_style(colors.outline)
+            .background_style(colors.background.mix(0.8))
+            .label_font(font(16.))
+            .draw()?;
+    }
+    root.present()?;
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use std::ops::{AddAssign, RemAssign, SubAssign};
+
+use rand::RngCore;
+
+use crate::distributions::*;
+use crate::sorts::*;
+
+/// A type-erased sorting method, usable without knowing the implementing type at compile time
+pub struct SortEntry<T> {
+    name: String,
+    stable: bool,
+    sort: fn(&mut [T]),
+}
+
+impl<T> SortEntry<T> {
+    pub fn new(name: impl Into<String>, stable: bool, sort: fn(&mut [T])) -> Self {
+        SortEntry {
+            name: name.into(),
+            stable,
+            sort,
+        }
+    }
+    pub fn of<S: SortingMethod<T>>() -> Self {
+        Self::new(S::name(), S::stable(), S::sort)
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn stable(&self) -> bool {
+        self.stable
+    }
+    pub fn sort(&self, data: &mut [T]) {
+        (self.sort)(data)
+    }
+}
+
+type Generator<T> = Box<dyn Fn(&mut dyn RngCore, usize) -> Vec<T>>;
+
+/// A type-erased input distribution, usable without knowing the implementing type at compile time
+pub struct DistributionEntry<T> {
+    name: String,
+    generate: Generator<T>,
+}
+
+impl<T: 'static> DistributionEntry<T> {
+    pub fn new(
+        name: impl Into<String>,
+        generate: impl Fn(&mut dyn RngCore, usize) -> Vec<T> + 'static,
+    ) -> Self {
+        DistributionEntry {
+            name: name.into(),
+            generate: Box::new(generate),
+        }
+    }
+    pub fn of<D: InputDistribution<T> + 'static>() -> Self {
+        Self::new(D::name(), |mut rng, len| D::generate(&mut rng, len))
+    }
+}
+
+impl<T> DistributionEntry<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn generate(&self, rng: &mut dyn RngCore, len: usize) -> Vec<T> {
+        (self.generate)(rng, len)
+    }
+}
+
+/// The set of sorting methods and input distributions a benchmark will run
+pub struct Registry<T> {
+    sorts: Vec<SortEntry<T>>,
+    distributions: Vec<DistributionEntry<T>>,
+}
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Registry {
+            sorts: Vec::new(),
+            distributions: Vec::new(),
+        }
+    }
+}
+
+impl<T> Registry<T> {
+    pub fn sorts(&self) -> &[SortEntry<T>] {
+        &self.sorts
+    }
+    pub fn distributions(&self) -> &[DistributionEntry<T>] {
+        &self.distributions
+    }
+}
+
+impl<T: 'static> Registry<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_sort<S: SortingMethod<T>>(mut self) -> Self {
+        self.add_sort(SortEntry::of::<S>());
+        self
+    }
+    pub fn with_distribution<D: InputDistribution<T> + 'static>(mut self) -> Self {
+        self.add_distribution(DistributionEntry::of::<D>());
+        self
+    }
+    /// Adds a sorting method, replacing any previously registered one with the same name
+    pub fn add_sort(&mut self, entry: SortEntry<T>) {
+        self.sorts.retain(|s| s.name != entry.name);
+        self.sorts.push(entry);
+    }
+    /// Adds an input distribution, replacing any previously registered one with the same name
+    pub fn add_distribution(&mut self, entry: DistributionEntry<T>) {
+        self.distributions.retain(|d| d.name != entry.name);
+        self.distributions.push(entry);
+    }
+    pub fn retain_sorts(&mut self, f: impl FnMut(&SortEntry<T>) -> bool) {
+        self.sorts.retain(f)
+    }
+    pub fn retain_distributions(&mut self, f: impl FnMut(&DistributionEntry<T>) -> bool) {
+        self.distributions.retain(f)
+    }
+}
+
+impl<T> Registry<T>
+where
+    T: num::PrimInt + AddAssign + SubAssign + RemAssign + 'static,
+    rand::distributions::Standard: rand::distributions::Distribution<T>,
+{
+    /// Every sorting method and input distribution shipped with sort_bench
+    pub fn builtin() -> Self {
+        Registry::new()
+            .with_sort::<RustStdSort>()
+            .with_sort::<QuickSort>()
+            .with_sort::<BubbleSort>()
+            .with_sort::<InsertionSort>()
+            .with_distribution::<Uniform>()
+            .with_distribution::<Sorted>()
+            .with_distribution::<Reverse>()
+            .with_distribution::<AllEqual>()
+            .with_distribution::<Shuffled>()
+            .with_distribution::<ShuffledValues<16>>()
+            .with_distribution::<AscendingDescending>()
+            .with_distribution::<PushFront>()
+            .with_distribution::<PushMiddle>()
+    }
+}
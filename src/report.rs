@@ -0,0 +1,197 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::alloc::MemoryStats;
+use crate::harness::{BenchmarkResult, CacheMode};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Metric {
+    /// Time taken to sort the whole input
+    WallTime,
+    /// Time stamp counter cycles per element, needs an x86 CPU with an invariant TSC
+    Cycles,
+    /// Time taken per element of the input
+    #[default]
+    TimePerElement,
+    /// The most heap memory allocated at once while sorting
+    PeakMemory,
+    /// Number of heap allocations made while sorting
+    Allocations,
+    /// Total heap memory allocated while sorting
+    BytesAllocated,
+}
+
+impl Metric {
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::WallTime => "Time (ns)",
+            Metric::Cycles => "Cycles per element",
+            Metric::TimePerElement => "Time per element (ns)",
+            Metric::PeakMemory => "Peak heap usage (bytes)",
+            Metric::Allocations => "Allocations",
+            Metric::BytesAllocated => "Bytes allocated",
+        }
+    }
+}
+
+/// Identifies a single benchmark case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CaseKey<'a> {
+    pub sort: &'a str,
+    pub distribution: &'a str,
+    pub length: usize,
+    pub element_type: &'a str,
+    pub cache: CacheMode,
+}
+
+/// The outcome of one sorting method applied to one input
+#[derive(Debug, Clone)]
+pub struct BenchmarkRecord {
+    pub sort: String,
+    pub stable: bool,
+    pub distribution: String,
+    pub length: usize,
+    pub element_type: String,
+    pub cache: CacheMode,
+    pub result: BenchmarkResult,
+}
+
+impl BenchmarkRecord {
+    pub fn key(&self) -> CaseKey<'_> {
+        CaseKey {
+            sort: &self.sort,
+            distribution: &self.distribution,
+            length: self.length,
+            element_type: &self.element_type,
+            cache: self.cache,
+        }
+    }
+    /// The value of `metric` for this case, cycles fall back to time per element when missing
+    pub fn metric(&self, metric: Metric) -> f64 {
+        let time = self.result.time.as_nanos() as f64;
+        let length = self.length.max(1) as f64;
+        match metric {
+            Metric::WallTime => time,
+            Metric::Cycles => match self.result.mean_cycles() {
+                Some(cycles) => cycles / length,
+                None => time / length,
+            },
+            Metric::TimePerElement => time / length,
+            Metric::PeakMemory => self.result.mean_memory(|m| m.peak_bytes),
+            Metric::Allocations => self.result.mean_memory(|m| m.allocations),
+            Metric::BytesAllocated => self.result.mean_memory(|m| m.bytes_allocated),
+        }
+    }
+    /// The value of `metric` for every run, cycles fall back to time per element when missing
+    pub fn samples(&self, metric: Metric) -> Vec<f64> {
+        let length = self.length.max(1) as f64;
+        let times = || self.result.samples.iter().map(|s| s.as_nanos() as f64);
+        let memory = |get: fn(&MemoryStats) -> usize| {
+            self.result.memory.iter().map(|m| get(m) as f64).collect()
+        };
+        match metric {
+            Metric::WallTime => times().collect(),
+            Metric::Cycles if !self.result.cycles.is_empty() => self
+                .result
+                .cycles
+                .iter()
+                .map(|&c| c as f64 / length)
+                .collect(),
+            Metric::Cycles | Metric::TimePerElement => times().map(|t| t / length).collect(),
+            Metric::PeakMemory => memory(|m| m.peak_bytes),
+            Metric::Allocations => memory(|m| m.allocations),
+            Metric::BytesAllocated => memory(|m| m.bytes_allocated),
+        }
+    }
+}
+
+/// Every case of a benchmark run.
+///
+/// Records are ordered by element type, then length. Within those, sorts and distributions keep
+/// the order they were registered in.
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkReport {
+    records: Vec<BenchmarkRecord>,
+}
+
+/// Deduplicates while keeping the order of first appearance
+fn unique<'a, T: PartialEq + 'a>(items: impl Iterator<Item = T>) -> Vec<T> {
+    let mut v = Vec::new();
+    for item in items {
+        if !v.contains(&item) {
+            v.push(item);
+        }
+    }
+    v
+}
+
+impl BenchmarkReport {
+    pub fn new(records: Vec<BenchmarkRecord>) -> Self {
+        let mut report = BenchmarkReport::default();
+        report.extend(records);
+        report
+    }
+    /// Adds records, replacing any existing ones for the same case
+    pub fn extend(&mut self, records: impl IntoIterator<Item = BenchmarkRecord>) {
+        for record in records {
+            match self.records.iter().position(|r| r.key() == record.key()) {
+                Some(idx) => self.records[idx] = record,
+                None => self.records.push(record),
+            }
+        }
+        // The sort is stable, which preserves the registration order of sorts and distributions
+        self.records
+            .sort_by(|a, b| (&a.element_type, a.length).cmp(&(&b.element_type, b.length)));
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, BenchmarkRecord> {
+        self.records.iter()
+    }
+    pub fn get(&self, key: CaseKey) -> Option<&BenchmarkRecord> {
+        self.records.iter().find(|r| r.key() == key)
+    }
+    pub fn element_types(&self) -> Vec<&str> {
+        unique(self.records.iter().map(|r| r.element_type.as_str()))
+    }
+    /// Every benchmarked length, in ascending order
+    pub fn lengths(&self) -> Vec<usize> {
+        let mut lengths = unique(self.records.iter().map(|r| r.length));
+        lengths.sort_unstable();
+        lengths
+    }
+    pub fn sorts(&self) -> Vec<&str> {
+        unique(self.records.iter().map(|r| r.sort.as_str()))
+    }
+    pub fn distributions(&self) -> Vec<&str> {
+        unique(self.records.iter().map(|r| r.distribution.as_str()))
+    }
+    /// Every cache mode, in the order they were run
+    pub fn cache_modes(&self) -> Vec<CacheMode> {
+        unique(self.records.iter().map(|r| r.cache))
+    }
+    pub fn failures(&self) -> impl Iterator<Item = &BenchmarkRecord> {
+        self.records.iter().filter(|r| !r.result.success)
+    }
+    /// The records matching `filter`, grouped by sort
+    pub fn by_sort(
+        &self,
+        mut filter: impl FnMut(&BenchmarkRecord) -> bool,
+    ) -> Vec<(&str, Vec<&BenchmarkRecord>)> {
+        let mut groups: Vec<(&str, Vec<&BenchmarkRecord>)> = Vec::new();
+        for record in self.records.iter().filter(|r| filter(r)) {
+            match groups.iter_mut().find(|(sort, _)| *sort == record.sort) {
+                Some((_, group)) => group.push(record),
+                None => groups.push((&record.sort, vec![record])),
+            }
+        }
+        groups
+    }
+}
+
+impl<'a> IntoIterator for &'a BenchmarkReport {
+    type Item = &'a BenchmarkRecord;
+    type IntoIter = std::slice::Iter<'a, BenchmarkRecord>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
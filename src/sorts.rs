@@ -104,7 +104,7 @@ fn quickersort<T: PartialOrd>(mut data: &mut [T]) {
         if slow != data.len() {
             std::mem::swap(&mut data[slow], pivot);
         }
-        return slow;
+        slow
     }
 
     unsafe fn swap_unchecked<T>(data: &mut [T], idx1: usize, idx2: usize) {
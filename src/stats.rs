@@ -0,0 +1,186 @@
+pub fn mean(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.;
+    }
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+/// Two-sided p-value of the Mann-Whitney U test, using the normal approximation with tie and
+/// continuity correction.
+///
+/// Makes no assumptions about the shape of the distributions, which suits timings with their long
+/// right tails. Needs a handful of samples on each side before it can report anything significant.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> f64 {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    if a.is_empty() || b.is_empty() {
+        return 1.;
+    }
+    let mut all: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&x| (x, true))
+        .chain(b.iter().map(|&x| (x, false)))
+        .collect();
+    all.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // Assign ranks, giving tied values the average of the ranks they span
+    let mut rank_sum_a = 0.;
+    let mut tie_correction = 0.;
+    let mut i = 0;
+    while i < all.len() {
+        let mut j = i;
+        while j < all.len() && all[j].0 == all[i].0 {
+            j += 1;
+        }
+        let rank = (i + j + 1) as f64 / 2.;
+        rank_sum_a += rank * all[i..j].iter().filter(|x| x.1).count() as f64;
+        let ties = (j - i) as f64;
+        tie_correction += ties.powi(3) - ties;
+        i = j;
+    }
+
+    let n = n1 + n2;
+    let u = rank_sum_a - n1 * (n1 + 1.) / 2.;
+    let mu = n1 * n2 / 2.;
+    let sigma = (n1 * n2 / 12. * ((n + 1.) - tie_correction / (n * (n - 1.)))).sqrt();
+    if sigma == 0. {
+        return 1.;
+    }
+    let z = ((u - mu).abs() - 0.5).max(0.) / sigma;
+    (2. * (1. - normal_cdf(z))).min(1.)
+}
+
+/// The smallest p-value [mann_whitney_u] can give for samples of these sizes, which it gives when
+/// every sample of one side is below every sample of the other
+pub fn min_p_value(n1: usize, n2: usize) -> f64 {
+    let a: Vec<f64> = (0..n1).map(|i| i as f64).collect();
+    let b: Vec<f64> = (n1..n1 + n2).map(|i| i as f64).collect();
+    mann_whitney_u(&a, &b)
+}
+
+/// The fewest samples per side with which [mann_whitney_u] can report a difference significant at
+/// level `alpha`
+pub fn runs_for_significance(alpha: f64) -> Option<usize> {
+    (1..=1000).find(|&n| min_p_value(n, n) < alpha)
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1. + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz and Stegun formula 7.1.26, accurate to 1.5e-7
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1. / (1. + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1. - poly * (-x * x).exp())
+}
+
+/// Least squares slope of `ys` against `xs`
+pub fn slope(xs: &[f64], ys: &[f64]) -> f64 {
+    let (mx, my) = (mean(xs), mean(ys));
+    let covariance: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mx) * (y - my)).sum();
+    let variance: f64 = xs.iter().map(|x| (x - mx).powi(2)).sum();
+    if variance == 0. {
+        return 0.;
+    }
+    covariance / variance
+}
+
+/// What a box plot shows, with the whiskers reaching the furthest samples within 1.5 times the
+/// interquartile range of the box
+#[derive(Debug, Clone)]
+pub struct BoxSummary {
+    pub low: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub high: f64,
+    /// The samples beyond the whiskers
+    pub outliers: Vec<f64>,
+}
+
+impl BoxSummary {
+    pub fn new(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let (q1, median, q3) = (
+            percentile(&sorted, 0.25),
+            percentile(&sorted, 0.5),
+            percentile(&sorted, 0.75),
+        );
+        let fence = 1.5 * (q3 - q1);
+        let (inside, outliers): (Vec<f64>, Vec<f64>) = sorted
+            .iter()
+            .partition(|&&s| s >= q1 - fence && s <= q3 + fence);
+        Some(BoxSummary {
+            low: inside.first().copied().unwrap_or(q1),
+            q1,
+            median,
+            q3,
+            high: inside.last().copied().unwrap_or(q3),
+            outliers,
+        })
+    }
+}
+
+/// Linearly interpolated percentile of sorted samples, `p` being a fraction
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separated_small_samples_are_not_significant() {
+        let p = mann_whitney_u(&[1., 2.], &[3., 4.]);
+        assert!((p - 0.245).abs() < 0.001, "p = {p}");
+        assert_eq!(p, min_p_value(2, 2));
+        assert!(min_p_value(3, 3) > 0.05);
+    }
+
+    #[test]
+    fn separated_samples_become_significant() {
+        let a: Vec<f64> = (0..10).map(f64::from).collect();
+        let b: Vec<f64> = (10..20).map(f64::from).collect();
+        assert!(mann_whitney_u(&a, &b) < 0.001);
+        assert_eq!(mann_whitney_u(&a, &b), mann_whitney_u(&b, &a));
+    }
+
+    #[test]
+    fn identical_samples_are_not_different() {
+        assert_eq!(mann_whitney_u(&[5., 5., 5.], &[5., 5., 5.]), 1.);
+        let p = mann_whitney_u(&[1., 2., 3.], &[1., 2., 3.]);
+        assert!(p > 0.999, "p = {p}");
+    }
+
+    #[test]
+    fn ties_lower_significance() {
+        let tied = mann_whitney_u(&[1., 2., 2., 3., 3.], &[3., 3., 4., 4., 5.]);
+        let separated = mann_whitney_u(&[1., 2., 2., 3., 3.], &[4., 4., 5., 5., 6.]);
+        assert!(tied > separated, "{tied} <= {separated}");
+        assert!(tied > 0. && tied < 1.);
+    }
+
+    #[test]
+    fn empty_samples_are_not_significant() {
+        assert_eq!(mann_whitney_u(&[], &[1., 2.]), 1.);
+        assert_eq!(min_p_value(0, 5), 1.);
+    }
+
+    #[test]
+    fn runs_for_significance_reaches_alpha() {
+        let runs = runs_for_significance(0.05).unwrap();
+        assert!(min_p_value(runs, runs) < 0.05);
+        assert!(min_p_value(runs - 1, runs - 1) >= 0.05);
+    }
+}
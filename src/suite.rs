@@ -0,0 +1,379 @@
+use std::{
+    ops::{AddAssign, RemAssign, SubAssign},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::distributions::{shuffled_values, shuffled_values_name};
+use crate::harness::{BenchOptions, CacheMode};
+use crate::isolate::Isolation;
+use crate::plot::{Chart, Theme};
+use crate::registry::{DistributionEntry, Registry};
+use crate::report::Metric;
+use crate::timer::Timer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElementType {
+    I32,
+    U32,
+    I64,
+    U64,
+}
+
+impl ElementType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ElementType::I32 => "i32",
+            ElementType::U32 => "u32",
+            ElementType::I64 => "i64",
+            ElementType::U64 => "u64",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// The charts selected with --chart, as PNG images
+    Png,
+    /// The charts selected with --chart, as SVG images that scale cleanly
+    Svg,
+    /// Every sample of every case in `results.json`
+    Json,
+    /// Every sample of every case in `results.csv`, one row per sample
+    Csv,
+    /// A single page in `report.html` with the run, then a table per length and the charts for
+    /// every element type
+    Html,
+    /// A table per length printed to stdout, see --bars
+    Table,
+    /// A table per length in `results.md`, for pasting into pull requests, see --reference
+    Markdown,
+}
+
+/// Everything needed to run a benchmark, either loaded from a TOML file or built from the
+/// command line
+///
+/// ```toml
+/// name = "Duplicate heavy"
+/// lengths = [1_000, 100_000]
+/// element_types = ["i32", "u64"]
+/// runs = 10
+/// sorts = ["Rust Standard library", "Verq's Quicksort"]
+/// distributions = ["All equal", { name = "Shuffled", values = 4 }]
+/// formats = ["svg", "json", "csv"]
+/// resolution = [2560, 1440]
+/// reference = "Rust Standard library"
+/// metric = "cycles"
+/// charts = ["bar"]
+/// log_scale = true
+/// isolation = "sort"
+/// cache = ["warm", "cold"]
+/// alignment = 4096
+/// pin_core = 2
+/// output = "images/duplicates"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suite {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default = "default_lengths")]
+    pub lengths: Vec<usize>,
+    #[serde(default = "default_element_types")]
+    pub element_types: Vec<ElementType>,
+    #[serde(default = "default_runs")]
+    pub runs: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Sorting methods to run, all of them if empty
+    #[serde(default)]
+    pub sorts: Vec<String>,
+    /// Input distributions to generate, all of them if empty
+    #[serde(default)]
+    pub distributions: Vec<DistributionSpec>,
+    #[serde(default)]
+    pub sort_filter: Option<String>,
+    #[serde(default)]
+    pub distribution_filter: Option<String>,
+    #[serde(default = "default_formats")]
+    pub formats: Vec<OutputFormat>,
+    #[serde(default)]
+    pub metric: Metric,
+    #[serde(default = "default_charts")]
+    pub charts: Vec<Chart>,
+    /// Whether bar and box charts plot the metric on a logarithmic axis
+    #[serde(default)]
+    pub log_scale: bool,
+    /// Whether to collect performance counters
+    #[serde(default)]
+    pub counters: bool,
+    #[serde(default)]
+    pub isolation: Isolation,
+    /// Every input is benchmarked in each of these modes
+    #[serde(default = "default_cache")]
+    pub cache: Vec<CacheMode>,
+    /// The alignment of the buffer inputs are sorted in, in bytes
+    #[serde(default = "default_alignment")]
+    pub alignment: usize,
+    /// The core to pin the benchmark thread to
+    #[serde(default)]
+    pub pin_core: Option<usize>,
+    /// Whether to lower the niceness of the benchmark as far as permitted
+    #[serde(default)]
+    pub raise_priority: bool,
+    #[serde(default)]
+    pub theme: Theme,
+    /// The sorting method every other one is compared against
+    #[serde(default)]
+    pub reference: Option<String>,
+    /// Whether the table format draws a bar next to every result
+    #[serde(default)]
+    pub bars: bool,
+    /// The size of the charts in pixels
+    #[serde(default = "default_resolution")]
+    pub resolution: (u32, u32),
+    #[serde(default = "default_output")]
+    pub output: PathBuf,
+}
+
+fn default_lengths() -> Vec<usize> {
+    vec![100, 1_000, 10_000]
+}
+fn default_element_types() -> Vec<ElementType> {
+    vec![ElementType::I32]
+}
+fn default_runs() -> usize {
+    2
+}
+fn default_charts() -> Vec<Chart> {
+    vec![Chart::Bar, Chart::Heatmap, Chart::Scaling]
+}
+fn default_cache() -> Vec<CacheMode> {
+    vec![CacheMode::Warm]
+}
+fn default_alignment() -> usize {
+    64
+}
+fn default_formats() -> Vec<OutputFormat> {
+    vec![OutputFormat::Png]
+}
+fn default_resolution() -> (u32, u32) {
+    (1920, 1080)
+}
+fn default_output() -> PathBuf {
+    PathBuf::from("images")
+}
+
+impl Default for Suite {
+    fn default() -> Self {
+        Suite {
+            name: None,
+            lengths: default_lengths(),
+            element_types: default_element_types(),
+            runs: default_runs(),
+            seed: None,
+            sorts: Vec::new(),
+            distributions: Vec::new(),
+            sort_filter: None,
+            distribution_filter: None,
+            formats: default_formats(),
+            metric: Metric::default(),
+            charts: default_charts(),
+            log_scale: false,
+            counters: false,
+            isolation: Isolation::default(),
+            cache: default_cache(),
+            alignment: default_alignment(),
+            pin_core: None,
+            raise_priority: false,
+            theme: Theme::default(),
+            reference: None,
+            bars: false,
+            resolution: default_resolution(),
+            output: default_output(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DistributionSpec {
+    Name(String),
+    Parameterized(DistributionParams),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DistributionParams {
+    pub name: String,
+    /// The number of distinct values, only supported by `Shuffled`
+    #[serde(default)]
+    pub values: Option<usize>,
+}
+
+impl DistributionParams {
+    fn entry<T>(&self) -> Result<Option<DistributionEntry<T>>, String>
+    where
+        T: num::PrimInt + AddAssign + RemAssign + 'static,
+    {
+        let Some(values) = self.values else {
+            return Ok(None);
+        };
+        if !self.name.eq_ignore_ascii_case("Shuffled") {
+            return Err(format!(
+                "input distribution `{}` does not take a `values` parameter",
+                self.name
+            ));
+        }
+        let modulus = T::from(values).ok_or_else(|| {
+            format!(
+                "{values} distinct values do not fit in {}",
+                std::any::type_name::<T>()
+            )
+        })?;
+        Ok(Some(DistributionEntry::new(
+            shuffled_values_name(values),
+            move |mut rng, len| shuffled_values(&mut rng, len, modulus),
+        )))
+    }
+}
+
+fn select<'a>(
+    kind: &str,
+    available: impl Iterator<Item = &'a str> + Clone,
+    wanted: &[String],
+) -> Result<Vec<String>, String> {
+    wanted
+        .iter()
+        .map(|want| {
+            available
+                .clone()
+                .find(|name| name.eq_ignore_ascii_case(want))
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    let names: Vec<_> = available.clone().collect();
+                    format!(
+                        "unknown {kind} `{want}`, expected one of: {}",
+                        names.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Case-insensitive glob match, patterns without wildcards match any name containing them
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    if !pattern.iter().any(|&c| c == '*' || c == '?') {
+        return pattern.is_empty() || name.windows(pattern.len()).any(|w| w == pattern);
+    }
+    glob(&pattern, &name)
+}
+
+fn glob(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| glob(rest, &name[skip..])),
+        Some((&p, rest)) => match name.split_first() {
+            Some((&c, name)) if p == '?' || p == c => glob(rest, name),
+            _ => false,
+        },
+    }
+}
+
+impl Suite {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read suite {}: {e}", path.display()))?;
+        let suite: Suite =
+            toml::from_str(&text).map_err(|e| format!("invalid suite {}: {e}", path.display()))?;
+        let invalid = |problem: String| Err(format!("invalid suite {}: {problem}", path.display()));
+        if !suite.alignment.is_power_of_two() {
+            return invalid(format!(
+                "alignment must be a power of two, got {}",
+                suite.alignment
+            ));
+        }
+        if suite.runs == 0 {
+            return invalid("runs must be at least 1".to_string());
+        }
+        if suite.resolution.0 == 0 || suite.resolution.1 == 0 {
+            let (width, height) = suite.resolution;
+            return invalid(format!("resolution must not be zero, got {width}x{height}"));
+        }
+        Ok(suite)
+    }
+
+    pub fn options(&self, seed: u64, timer: Timer) -> BenchOptions {
+        BenchOptions {
+            seed,
+            lengths: self.lengths.clone(),
+            runs: self.runs,
+            timer,
+            counters: self.counters,
+            cache: self.cache.clone(),
+            alignment: self.alignment,
+        }
+    }
+
+    /// The builtin registry narrowed down to the sorts and distributions this suite asks for
+    pub fn registry<T>(&self) -> Result<Registry<T>, String>
+    where
+        T: num::PrimInt + AddAssign + SubAssign + RemAssign + 'static,
+        rand::distributions::Standard: rand::distributions::Distribution<T>,
+    {
+        let mut registry = Registry::<T>::builtin();
+        let mut wanted = Vec::with_capacity(self.distributions.len());
+        for spec in &self.distributions {
+            match spec {
+                DistributionSpec::Name(name) => wanted.push(name.clone()),
+                DistributionSpec::Parameterized(params) => match params.entry()? {
+                    Some(entry) => {
+                        wanted.push(entry.name().to_string());
+                        registry.add_distribution(entry);
+                    }
+                    None => wanted.push(params.name.clone()),
+                },
+            }
+        }
+        let sorts = select(
+            "sorting method",
+            registry.sorts().iter().map(|s| s.name()),
+            &self.sorts,
+        )?;
+        let distributions = select(
+            "input distribution",
+            registry.distributions().iter().map(|d| d.name()),
+            &wanted,
+        )?;
+        let sort_pattern = self.sort_filter.as_deref().unwrap_or("");
+        let distribution_pattern = self.distribution_filter.as_deref().unwrap_or("");
+        registry.retain_sorts(|s| {
+            (sorts.is_empty() || sorts.iter().any(|n| n == s.name()))
+                && matches(sort_pattern, s.name())
+        });
+        registry.retain_distributions(|d| {
+            (distributions.is_empty() || distributions.iter().any(|n| n == d.name()))
+                && matches(distribution_pattern, d.name())
+        });
+        if self.charts.contains(&Chart::Speedup) && self.reference.is_none() {
+            return Err("the speedup chart needs a reference sort, see --reference".to_string());
+        }
+        if let Some(reference) = &self.reference {
+            let sorts = registry.sorts().iter().map(|s| s.name());
+            if !sorts.clone().any(|s| s.eq_ignore_ascii_case(reference)) {
+                let names: Vec<_> = sorts.collect();
+                return Err(format!(
+                    "reference sort `{reference}` is not benchmarked, expected one of: {}",
+                    names.join(", ")
+                ));
+            }
+        }
+        Ok(registry)
+    }
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+
+/// The state of the machine when the benchmark ran
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub cpu_model: Option<String>,
+    pub cpu_count: Option<usize>,
+    /// The core the benchmark was pinned to
+    pub pinned_core: Option<usize>,
+    /// The niceness the benchmark ran at
+    pub nice: Option<i32>,
+    pub governor: Option<String>,
+    pub frequency_mhz: Option<u64>,
+    pub last_level_cache_bytes: Option<usize>,
+    /// Over the last 1, 5 and 15 minutes
+    pub load_average: Option<[f64; 3]>,
+    pub timer: String,
+}
+
+impl RunMetadata {
+    /// Describes the machine as seen from `core`, or the first core if not pinned
+    pub fn collect(pinned_core: Option<usize>, timer: String) -> Self {
+        let core = pinned_core.unwrap_or(0);
+        let cpufreq = |file: &str| {
+            let path = format!("/sys/devices/system/cpu/cpu{core}/cpufreq/{file}");
+            std::fs::read_to_string(path)
+                .ok()
+                .map(|s| s.trim().to_string())
+        };
+        RunMetadata {
+            cpu_model: std::fs::read_to_string("/proc/cpuinfo")
+                .ok()
+                .and_then(|info| {
+                    info.lines()
+                        .find(|l| l.starts_with("model name"))
+                        .and_then(|l| l.split_once(':'))
+                        .map(|(_, model)| model.trim().to_string())
+                }),
+            cpu_count: std::thread::available_parallelism().ok().map(|n| n.get()),
+            pinned_core,
+            nice: sys::nice(),
+            governor: cpufreq("scaling_governor"),
+            frequency_mhz: cpufreq("scaling_cur_freq")
+                .and_then(|khz| khz.parse::<u64>().ok())
+                .map(|khz| khz / 1000),
+            last_level_cache_bytes: crate::cache::last_level_cache_bytes(),
+            load_average: std::fs::read_to_string("/proc/loadavg")
+                .ok()
+                .and_then(|load| {
+                    let mut fields = load.split_whitespace().map(|f| f.parse().ok());
+                    Some([fields.next()??, fields.next()??, fields.next()??])
+                }),
+            timer,
+        }
+    }
+
+    /// Reasons to distrust the measurements
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(governor) = self.governor.as_deref().filter(|&g| g != "performance") {
+            warnings.push(format!(
+                "the CPU frequency governor is `{governor}`, use `performance` for stable clocks"
+            ));
+        }
+        if let Some([load, ..]) = self.load_average {
+            if load > 1. {
+                warnings.push(format!(
+                    "the 1 minute load average is {load:.2}, other processes are competing for the CPU"
+                ));
+            }
+        }
+        if self.pinned_core.is_none() {
+            warnings.push("the benchmark is not pinned to a core, see --pin-core".to_string());
+        }
+        warnings
+    }
+}
+
+/// Pins the thread and raises its priority as requested, returning the core it was pinned to and
+/// anything that failed along the way
+pub fn prepare(pin_core: Option<usize>, raise: bool) -> (Option<usize>, Vec<String>) {
+    let mut problems = Vec::new();
+    let pinned = pin_core.and_then(|core| match pin_to_core(core) {
+        Ok(()) => Some(core),
+        Err(e) => {
+            problems.push(e);
+            None
+        }
+    });
+    if raise {
+        if let Err(e) = raise_priority() {
+            problems.push(e);
+        }
+    }
+    (pinned, problems)
+}
+
+/// Pins the calling thread to `core`
+pub fn pin_to_core(core: usize) -> Result<(), String> {
+    sys::pin_to_core(core)
+}
+
+/// Lowers the niceness of the process as far as it is allowed to, returning the niceness reached
+pub fn raise_priority() -> Result<i32, String> {
+    sys::raise_priority()
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    pub fn pin_to_core(core: usize) -> Result<(), String> {
+        if core >= libc::CPU_SETSIZE as usize {
+            return Err(format!("core {core} is out of range"));
+        }
+        // SAFETY: cpu_set_t is plain data, so all zeroes is a valid empty set, and core was
+        // checked to fit in it
+        let result = unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(core, &mut set);
+            libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+        };
+        if result != 0 {
+            return Err(format!(
+                "failed to pin to core {core}: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn raise_priority() -> Result<i32, String> {
+        let current = nice().unwrap_or(0);
+        // Without CAP_SYS_NICE only raising the niceness is allowed, so try from the lowest down
+        for niceness in -20..current {
+            // SAFETY: setpriority has no memory safety requirements
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) } == 0 {
+                return Ok(niceness);
+            }
+        }
+        Err(format!(
+            "not permitted to lower the niceness below {current}: {}",
+            std::io::Error::last_os_error()
+        ))
+    }
+
+    pub fn nice() -> Option<i32> {
+        // getpriority can legitimately return -1, errno tells it apart from an error
+        // SAFETY: getpriority and errno have no memory safety requirements
+        unsafe {
+            *libc::__errno_location() = 0;
+            let niceness = libc::getpriority(libc::PRIO_PROCESS, 0);
+            (*libc::__errno_location() == 0).then_some(niceness)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    pub fn pin_to_core(_: usize) -> Result<(), String> {
+        Err("pinning to a core is only supported on Linux".to_string())
+    }
+    pub fn raise_priority() -> Result<i32, String> {
+        Err("raising the priority is only supported on Linux".to_string())
+    }
+    pub fn nice() -> Option<i32> {
+        None
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::IsTerminal;
+
+use crate::report::{BenchmarkRecord, BenchmarkReport, CaseKey, Metric};
+
+/// The length of the longest bar, in cells
+const BAR_WIDTH: usize = 40;
+/// Bars are drawn in eighths of a cell, these are one to seven eighths
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+const FASTEST: &str = "\x1b[1;32m";
+const FAILED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Prints a table per element type, cache mode and length with the metric of every sort on every distribution,
+/// optionally with a bar for each. The fastest sort of each distribution is highlighted when
+/// stdout is a terminal and `NO_COLOR` isn't set.
+pub fn print_tables(report: &BenchmarkReport, metric: Metric, bars: bool) {
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let sort_width = report
+        .sorts()
+        .iter()
+        .map(|s| s.chars().count())
+        .max()
+        .unwrap_or(0);
+    let sorts = report.sorts();
+    let cache_modes = report.cache_modes();
+    for element_type in report.element_types() {
+        for &cache in &cache_modes {
+            for length in report.lengths() {
+                let regime = if cache_modes.len() > 1 {
+                    format!(" with a {} cache", cache.name())
+                } else {
+                    String::new()
+                };
+                println!(
+                    "{length} {element_type} elements{regime}, {}",
+                    metric.label()
+                );
+                for distribution in report.distributions() {
+                    let records: Vec<&BenchmarkRecord> = sorts
+                        .iter()
+                        .filter_map(|&sort| {
+                            report.get(CaseKey {
+                                sort,
+                                distribution,
+                                length,
+                                element_type,
+                                cache,
+                            })
+                        })
+                        .collect();
+                    if records.is_empty() {
+                        continue;
+                    }
+                    let values: Vec<f64> = records.iter().map(|r| r.metric(metric)).collect();
+                    let fastest = records
+                        .iter()
+                        .zip(&values)
+                        .filter(|(r, _)| r.result.success)
+                        .map(|(_, &v)| v)
+                        .fold(f64::INFINITY, f64::min);
+                    let slowest = values.iter().copied().fold(0., f64::max);
+                    println!("  {distribution}");
+                    for (record, value) in records.into_iter().zip(values) {
+                        let (text, highlight) = if !record.result.success {
+                            ("failed".to_string(), Some(FAILED))
+                        } else {
+                            (format!("{value:.2}"), (value == fastest).then_some(FASTEST))
+                        };
+                        let mut line = format!("    {:sort_width$}  {text:>12}", record.sort);
+                        if bars && record.result.success && slowest > 0. {
+                            line.push_str("  ");
+                            line.push_str(&bar(value / slowest));
+                        }
+                        match highlight {
+                            Some(code) if color => println!("{code}{line}{RESET}"),
+                            _ => println!("{line}"),
+                        }
+                    }
+                }
+                println!();
+            }
+        }
+    }
+}
+
+/// A bar `fraction` of [BAR_WIDTH] long
+fn bar(fraction: f64) -> String {
+    let eighths = (fraction.clamp(0., 1.) * (BAR_WIDTH * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if let Some(&partial) = (eighths % 8)
+        .checked_sub(1)
+        .and_then(|idx| EIGHTHS.get(idx))
+    {
+        bar.push(partial);
+    }
+    bar
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// How the harness measures each run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Timer {
+    /// The monotonic OS clock, no cycle counts are recorded
+    Instant,
+    /// The x86 time stamp counter, with its frequency measured against the OS clock
+    Tsc { ticks_per_ns: f64 },
+}
+
+/// A single timed run
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub time: Duration,
+    pub cycles: Option<u64>,
+}
+
+impl Timer {
+    /// Uses the time stamp counter if it ticks at a constant rate, the OS clock otherwise
+    pub fn detect() -> Self {
+        if !tsc::invariant() {
+            return Timer::Instant;
+        }
+        // Spin instead of sleeping, so the CPU stays at the frequency it's benchmarked at
+        const CALIBRATION: Duration = Duration::from_millis(50);
+        let start = Instant::now();
+        let start_ticks = tsc::read();
+        while start.elapsed() < CALIBRATION {}
+        let ticks = tsc::read() - start_ticks;
+        let ns = start.elapsed().as_nanos() as f64;
+        Timer::Tsc {
+            ticks_per_ns: ticks as f64 / ns,
+        }
+    }
+
+    pub fn measure(&self, f: impl FnOnce()) -> Measurement {
+        match *self {
+            Timer::Instant => {
+                let start = Instant::now();
+                f();
+                Measurement {
+                    time: start.elapsed(),
+                    cycles: None,
+                }
+            }
+            Timer::Tsc { ticks_per_ns } => {
+                let start = tsc::read();
+                f();
+                let ticks = tsc::read() - start;
+                Measurement {
+                    time: Duration::from_nanos((ticks as f64 / ticks_per_ns) as u64),
+                    cycles: Some(ticks),
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Timer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timer::Instant => write!(f, "the OS clock"),
+            Timer::Tsc { ticks_per_ns } => {
+                write!(f, "the time stamp counter at {ticks_per_ns:.3} GHz")
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod tsc {
+    use std::arch::x86_64::{__cpuid, _mm_lfence, _rdtsc};
+
+    /// Whether the TSC runs at a constant rate regardless of frequency scaling and sleep states
+    pub fn invariant() -> bool {
+        if __cpuid(0x8000_0000).eax < 0x8000_0007 {
+            return false;
+        }
+        __cpuid(0x8000_0007).edx & (1 << 8) != 0
+    }
+
+    #[inline(always)]
+    pub fn read() -> u64 {
+        // SAFETY: rdtsc and lfence are available on every x86_64 CPU, the fences keep the read
+        // from being reordered around the code being measured
+        unsafe {
+            _mm_lfence();
+            let ticks = _rdtsc();
+            _mm_lfence();
+            ticks
+        }
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+mod tsc {
+    pub fn invariant() -> bool {
+        false
+    }
+    pub fn read() -> u64 {
+        unreachable!("there is no time stamp counter on this architecture")
+    }
+}
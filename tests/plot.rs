@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use sort_bench::harness::{BenchmarkResult, CacheMode};
+use sort_bench::plot::{self, Chart, ChartOptions, ImageFormat, Target, Theme};
+use sort_bench::report::{BenchmarkRecord, BenchmarkReport, Metric};
+
+fn record(sort: &str, distribution: &str, nanos: u64) -> BenchmarkRecord {
+    BenchmarkRecord {
+        sort: sort.to_string(),
+        stable: false,
+        distribution: distribution.to_string(),
+        length: 100,
+        element_type: "i32".to_string(),
+        cache: CacheMode::Warm,
+        result: BenchmarkResult {
+            time: Duration::from_nanos(nanos),
+            samples: vec![Duration::from_nanos(nanos)],
+            cycles: Vec::new(),
+            counters: Vec::new(),
+            memory: Vec::new(),
+            success: true,
+        },
+    }
+}
+
+fn render(report: &BenchmarkReport, charts: &[Chart]) -> Vec<(String, String)> {
+    let options = ChartOptions {
+        charts,
+        theme: Theme::Dark,
+        metric: Metric::WallTime,
+        reference: None,
+        log_scale: false,
+    };
+    plot::render_svg(report, &options, (800, 600)).unwrap()
+}
+
+/// The filled rectangles of an SVG with a positive area, other than the background
+fn filled_cells(svg: &str) -> usize {
+    let attribute = |rect: &str, name: &str| {
+        let start = rect.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+        rect[start..].split('"').next().unwrap().to_string()
+    };
+    svg.split("<rect")
+        .skip(1)
+        .filter(|rect| attribute(rect, "fill") != "none")
+        .filter(|rect| {
+            let size = |name| attribute(rect, name).parse::<u32>().unwrap();
+            size("width") > 0 && size("height") > 0
+        })
+        .count()
+        - 1
+}
+
+fn heatmap(report: &BenchmarkReport) -> String {
+    let svgs = render(report, &[Chart::Heatmap]);
+    assert_eq!(svgs.len(), 1);
+    svgs.into_iter().next().unwrap().1
+}
+
+#[test]
+fn heatmap_of_one_sort() {
+    let report = BenchmarkReport::new(vec![
+        record("Only sort", "Sorted", 100),
+        record("Only sort", "Reversed", 200),
+        record("Only sort", "Shuffled", 300),
+    ]);
+    assert_eq!(filled_cells(&heatmap(&report)), 3);
+}
+
+#[test]
+fn heatmap_of_one_distribution() {
+    let report = BenchmarkReport::new(vec![
+        record("First", "Sorted", 100),
+        record("Second", "Sorted", 200),
+    ]);
+    assert_eq!(filled_cells(&heatmap(&report)), 2);
+}
+
+#[test]
+fn heatmap_of_one_case() {
+    let report = BenchmarkReport::new(vec![record("Only sort", "Sorted", 100)]);
+    assert_eq!(filled_cells(&heatmap(&report)), 1);
+}
+
+#[test]
+fn heatmap_of_every_case() {
+    let report = BenchmarkReport::new(vec![
+        record("First", "Sorted", 100),
+        record("Second", "Sorted", 200),
+        record("First", "Reversed", 300),
+        record("Second", "Reversed", 400),
+    ]);
+    assert_eq!(filled_cells(&heatmap(&report)), 4);
+}
+
+#[test]
+fn scaling_skips_empty_inputs() {
+    let at = |length, nanos| BenchmarkRecord {
+        length,
+        ..record("Only sort", "Sorted", nanos)
+    };
+    let report = BenchmarkReport::new(vec![at(0, 50), at(10, 100), at(100, 1000)]);
+    let svgs = render(&report, &[Chart::Scaling]);
+    assert_eq!(svgs.len(), 1);
+    let (name, svg) = &svgs[0];
+    assert_eq!(name, "scaling");
+    // Only the two non-empty lengths are plotted
+    assert_eq!(svg.matches("<circle").count(), 2);
+
+    let report = BenchmarkReport::new(vec![at(0, 50), at(10, 100)]);
+    assert!(render(&report, &[Chart::Scaling]).is_empty());
+}
+
+#[test]
+fn draw_writes_every_chart() {
+    let report = BenchmarkReport::new(vec![
+        record("First", "Sorted", 100),
+        record("Second", "Sorted", 200),
+    ]);
+    let options = ChartOptions {
+        charts: &[Chart::Bar, Chart::Heatmap],
+        theme: Theme::Light,
+        metric: Metric::WallTime,
+        reference: None,
+        log_scale: false,
+    };
+    let dir = std::env::temp_dir().join(format!("sort_bench_draw_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let target = Target {
+        dir: dir.clone(),
+        format: ImageFormat::Svg,
+        resolution: (800, 600),
+    };
+    let written = plot::draw(&report, &options, &target).unwrap();
+    let rendered = plot::render_svg(&report, &options, target.resolution).unwrap();
+    assert_eq!(written.len(), rendered.len());
+    for (path, (name, svg)) in written.iter().zip(&rendered) {
+        assert_eq!(path, &dir.join(format!("{name}.svg")));
+        assert_eq!(&std::fs::read_to_string(path).unwrap(), svg);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}